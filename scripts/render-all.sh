@@ -20,12 +20,16 @@ while read -r rev; do
   echo "$entry" >> "$readme"
 
   n=$((n+1))
-  [ -f "$ppm" ] || {
+  [ -f "progress/$png" ] || {
     git checkout --quiet "$rev"
-    cargo run --release > "$ppm"
+    if git cat-file -e "$rev:src/output.rs" 2>/dev/null; then
+      cargo run --release -- -o "progress/$png"
+    else
+      # Older revisions only write PPM, to stdout
+      cargo run --release > "$ppm"
+      convert "$ppm" "progress/$png"
+    fi
   }
-
-  [ -f "progress/$png" ] || convert "$ppm" "progress/$png"
 done
 
 git checkout --quiet main
@@ -0,0 +1,119 @@
+//! Radiance arriving from beyond the scene, for rays that hit nothing
+
+use rand::prelude::SmallRng;
+
+use crate::{
+    ray::Ray,
+    vec3::{Color, Vec3},
+};
+
+mod environment;
+
+pub use environment::*;
+
+/// A direction chosen by `Background::sample`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BackgroundSample {
+    pub direction: Vec3,
+    /// Radiance arriving from `direction`
+    pub radiance: Color,
+    /// Density of choosing `direction`, with respect to solid angle
+    pub pdf: f64,
+}
+
+pub trait Background {
+    /// Radiance seen along `ray` after it left the scene
+    fn color(&self, ray: Ray) -> Color;
+
+    /// Choose a direction towards the background, preferring bright ones.
+    /// The renderer aims a shadow ray this way at every diffuse hit (see
+    /// `renderer::ray_color`). Backgrounds that are not worth sampling
+    /// directly return `None`.
+    fn sample(&self, _rng: &mut SmallRng) -> Option<BackgroundSample> {
+        None
+    }
+
+    /// Density of `sample` choosing `direction`
+    fn pdf(&self, _direction: Vec3) -> f64 {
+        0.0
+    }
+}
+
+/// The same color in every direction
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Constant {
+    color: Color,
+}
+
+impl Constant {
+    pub fn new(color: Color) -> Self {
+        Self { color }
+    }
+}
+
+impl Background for Constant {
+    fn color(&self, _ray: Ray) -> Color {
+        self.color
+    }
+}
+
+/// Blends linearly from `bottom`, looking straight down, to `top`, looking
+/// straight up
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Gradient {
+    bottom: Color,
+    top: Color,
+}
+
+impl Gradient {
+    pub const fn new(bottom: Color, top: Color) -> Self {
+        Self { bottom, top }
+    }
+
+    /// White to light blue
+    pub const fn sky() -> Self {
+        Self::new(Vec3(1.0, 1.0, 1.0), Vec3(0.5, 0.7, 1.0))
+    }
+}
+
+impl Default for Gradient {
+    fn default() -> Self {
+        Self::sky()
+    }
+}
+
+impl Background for Gradient {
+    fn color(&self, ray: Ray) -> Color {
+        let unit_direction = ray.direction.unit_vector();
+        let t = 0.5 * (unit_direction.y() + 1.0);
+        (1.0 - t) * self.bottom + t * self.top
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vec3::Point3;
+
+    fn ray(direction: Vec3) -> Ray {
+        Ray {
+            origin: Point3::new(0, 0, 0),
+            direction,
+            time: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_gradient() {
+        let gradient = Gradient::new(Color::new(0, 0, 0), Color::new(1, 2, 4));
+        assert_eq!(
+            Color::new(0, 0, 0),
+            gradient.color(ray(Vec3::new(0, -2, 0)))
+        );
+        assert_eq!(Color::new(1, 2, 4), gradient.color(ray(Vec3::new(0, 3, 0))));
+        assert_eq!(
+            Color::new(0.5, 1, 2),
+            gradient.color(ray(Vec3::new(1, 0, 0)))
+        );
+    }
+}
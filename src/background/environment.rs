@@ -0,0 +1,270 @@
+use std::{
+    f64::consts::PI,
+    fmt::Display,
+    path::{Path, PathBuf},
+};
+
+use rand::{prelude::SmallRng, Rng};
+
+use crate::{
+    degrees_to_radians,
+    distribution::Distribution2D,
+    ray::Ray,
+    vec3::{Color, Vec3},
+};
+
+use super::{Background, BackgroundSample};
+
+#[derive(Debug)]
+pub enum Error {
+    Load {
+        path: PathBuf,
+        source: image::ImageError,
+    },
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Load { path, source } => write!(f, "{}: {}", path.display(), source),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Load { source, .. } => Some(source),
+        }
+    }
+}
+
+/// Light from an equirectangular panorama surrounding the scene.
+///
+/// The center of the image lies in the -z direction, its top row straight
+/// up. Directions are importance sampled in proportion to the luminance of
+/// the pixels, so diffuse surfaces lit by a small, bright sun converge
+/// without fireflies.
+pub struct EnvironmentMap {
+    width: usize,
+    height: usize,
+    /// Linear colors, row by row from the top
+    pixels: Vec<Color>,
+    /// Around the y axis, in radians
+    rotation: f64,
+    intensity: f64,
+    distribution: Distribution2D,
+}
+
+impl EnvironmentMap {
+    /// Load a panorama in any format the `image` crate can read, typically
+    /// Radiance HDR or OpenEXR.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let image = image::open(path)
+            .map_err(|source| Error::Load {
+                path: path.to_path_buf(),
+                source,
+            })?
+            .into_rgb32f();
+
+        let pixels = image
+            .pixels()
+            .map(|p| Color::new(p[0], p[1], p[2]))
+            .collect();
+        Ok(Self::new(
+            image.width() as usize,
+            image.height() as usize,
+            pixels,
+        ))
+    }
+
+    /// Panics if the number of pixels doesn't match the size, or any pixel
+    /// is negative.
+    pub fn new(width: usize, height: usize, pixels: Vec<Color>) -> Self {
+        assert!(width > 0 && height > 0, "environment map must not be empty");
+        assert_eq!(width * height, pixels.len());
+        assert!(
+            pixels
+                .iter()
+                .all(|c| c.x() >= 0.0 && c.y() >= 0.0 && c.z() >= 0.0),
+            "environment map must not have negative colors"
+        );
+
+        // Rows near the poles cover less solid angle, so weigh them by
+        // sin(theta) to sample in proportion to the light they emit.
+        let weights: Vec<f64> = pixels
+            .chunks(width)
+            .enumerate()
+            .flat_map(|(row, pixels)| {
+                let sin_theta = (PI * (row as f64 + 0.5) / height as f64).sin();
+                pixels.iter().map(move |c| c.luminance() * sin_theta)
+            })
+            .collect();
+        let distribution = Distribution2D::new(&weights, width, height);
+
+        Self {
+            width,
+            height,
+            pixels,
+            rotation: 0.0,
+            intensity: 1.0,
+            distribution,
+        }
+    }
+
+    /// Rotate the panorama counter-clockwise around the y axis
+    pub fn with_rotation(mut self, degrees: f64) -> Self {
+        self.rotation = degrees_to_radians(degrees);
+        self
+    }
+
+    /// Scale the brightness of the panorama
+    pub fn with_intensity(mut self, intensity: f64) -> Self {
+        self.intensity = intensity;
+        self
+    }
+
+    /// Image coordinates in `[0, 1)²`, with `v` pointing down
+    fn direction_to_uv(&self, direction: Vec3) -> (f64, f64) {
+        let d = direction.unit_vector();
+        let theta = d.y().clamp(-1.0, 1.0).acos();
+        let phi = d.x().atan2(-d.z()) - self.rotation;
+        let u = (0.5 + phi / (2.0 * PI)).rem_euclid(1.0);
+        (u, theta / PI)
+    }
+
+    fn uv_to_direction(&self, (u, v): (f64, f64)) -> Vec3 {
+        let theta = v * PI;
+        let phi = (u - 0.5) * 2.0 * PI + self.rotation;
+        Vec3::new(
+            theta.sin() * phi.sin(),
+            theta.cos(),
+            -theta.sin() * phi.cos(),
+        )
+    }
+
+    fn lookup(&self, (u, v): (f64, f64)) -> Color {
+        let x = ((u * self.width as f64) as usize).min(self.width - 1);
+        let y = ((v * self.height as f64) as usize).min(self.height - 1);
+        self.intensity * self.pixels[y * self.width + x]
+    }
+
+    /// Convert a density over the image to one over solid angle
+    fn solid_angle_pdf(pdf: f64, v: f64) -> f64 {
+        let sin_theta = (v * PI).sin();
+        if sin_theta <= 0.0 {
+            0.0
+        } else {
+            pdf / (2.0 * PI * PI * sin_theta)
+        }
+    }
+}
+
+impl Background for EnvironmentMap {
+    fn color(&self, ray: Ray) -> Color {
+        self.lookup(self.direction_to_uv(ray.direction))
+    }
+
+    fn sample(&self, rng: &mut SmallRng) -> Option<BackgroundSample> {
+        let (uv, pdf) = self.distribution.sample(rng.gen(), rng.gen());
+        let pdf = Self::solid_angle_pdf(pdf, uv.1);
+        if pdf == 0.0 {
+            return None;
+        }
+
+        Some(BackgroundSample {
+            direction: self.uv_to_direction(uv),
+            radiance: self.lookup(uv),
+            pdf,
+        })
+    }
+
+    fn pdf(&self, direction: Vec3) -> f64 {
+        let uv = self.direction_to_uv(direction);
+        Self::solid_angle_pdf(self.distribution.pdf(uv), uv.1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::vec3::Point3;
+
+    fn ray(direction: Vec3) -> Ray {
+        Ray {
+            origin: Point3::new(0, 0, 0),
+            direction,
+            time: 0.0,
+        }
+    }
+
+    /// 4x2 map that is black except for one bright pixel
+    fn sun() -> EnvironmentMap {
+        let mut pixels = vec![Color::new(0, 0, 0); 8];
+        pixels[1] = Color::new(100, 100, 100);
+        EnvironmentMap::new(4, 2, pixels)
+    }
+
+    #[test]
+    fn test_color() {
+        let pixels = (0..8).map(|i| Color::new(i, 0, 0)).collect();
+        let map = EnvironmentMap::new(4, 2, pixels);
+
+        // -z is in the middle, x a quarter further to the right
+        assert_eq!(Color::new(2, 0, 0), map.color(ray(Vec3::new(0, 0.1, -1))));
+        assert_eq!(Color::new(3, 0, 0), map.color(ray(Vec3::new(1, 0.1, 0))));
+        assert_eq!(Color::new(4, 0, 0), map.color(ray(Vec3::new(0, -0.1, 1))));
+        assert_eq!(Color::new(5, 0, 0), map.color(ray(Vec3::new(-1, -0.1, 0))));
+
+        let map = map.with_rotation(90.0).with_intensity(2.0);
+        assert_eq!(Color::new(4, 0, 0), map.color(ray(Vec3::new(1, 0.1, 0))));
+    }
+
+    #[test]
+    fn test_sample() {
+        let map = sun().with_rotation(30.0);
+        let mut rng = SmallRng::seed_from_u64(0);
+
+        for _ in 0..100 {
+            let sample = map.sample(&mut rng).unwrap();
+            // Only the bright pixel is ever chosen
+            assert_eq!(Color::new(100, 100, 100), sample.radiance);
+            assert_eq!(sample.radiance, map.color(ray(sample.direction)));
+            assert!((sample.pdf - map.pdf(sample.direction)).abs() < 1e-9);
+        }
+
+        assert_eq!(0.0, map.pdf(Vec3::new(0, -1, 0.1)));
+    }
+
+    #[test]
+    fn test_sample_integral() {
+        // Estimating the integral of a uniform map over the sphere
+        let map = EnvironmentMap::new(8, 4, vec![Color::new(1, 1, 1); 32]);
+        let mut rng = SmallRng::seed_from_u64(0);
+        let n = 10_000;
+        let estimate: f64 = (0..n)
+            .map(|_| {
+                let sample = map.sample(&mut rng).unwrap();
+                sample.radiance.x() / sample.pdf
+            })
+            .sum::<f64>()
+            / n as f64;
+
+        assert!(
+            (estimate - 4.0 * PI).abs() < 0.05 * 4.0 * PI,
+            "{}",
+            estimate
+        );
+    }
+
+    #[test]
+    fn test_black() {
+        let map = EnvironmentMap::new(2, 2, vec![Color::new(0, 0, 0); 4]);
+        let mut rng = SmallRng::seed_from_u64(0);
+        let sample = map.sample(&mut rng).unwrap();
+        assert_eq!(Color::new(0, 0, 0), sample.radiance);
+    }
+}
@@ -1,4 +1,4 @@
-use rand::prelude::SmallRng;
+use rand::{prelude::SmallRng, Rng};
 
 use crate::{
     degrees_to_radians,
@@ -14,6 +14,8 @@ pub struct Camera {
     u: Vec3,
     v: Vec3,
     lens_radius: f64,
+    /// Shutter open and close times
+    shutter: (f64, f64),
 }
 
 impl Camera {
@@ -49,9 +51,25 @@ impl Camera {
             u,
             v,
             lens_radius,
+            shutter: (0.0, 0.0),
         }
     }
 
+    /// Keep the shutter open from `open` to `close`, sending rays at random
+    /// times in between. Objects moving during that time are blurred.
+    pub fn with_shutter(mut self, open: f64, close: f64) -> Self {
+        self.shutter = (open, close);
+        self
+    }
+
+    /// Change the aspect ratio, keeping the vertical field of view and the
+    /// center of the view.
+    pub fn set_aspect_ratio(&mut self, aspect_ratio: f64) {
+        let center = self.lower_left_corner + self.horizontal / 2.0 + self.vertical / 2.0;
+        self.horizontal = aspect_ratio * self.vertical.length() * self.u;
+        self.lower_left_corner = center - self.horizontal / 2.0 - self.vertical / 2.0;
+    }
+
     pub fn get_ray(&self, s: f64, t: f64, rng: &mut SmallRng) -> Ray {
         let &Camera {
             origin,
@@ -61,16 +79,80 @@ impl Camera {
             lens_radius,
             u,
             v,
+            shutter: (open, close),
             ..
         } = self;
 
         let rd = lens_radius * Vec3::random_in_unit_disk(rng);
         let offset = u * rd.x() + v * rd.y();
+        let time = if close > open {
+            rng.gen_range(open..close)
+        } else {
+            open
+        };
 
         let direction = lower_left_corner + s * horizontal + t * vertical - origin - offset;
         Ray {
             origin: origin + offset,
             direction,
+            time,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use super::*;
+
+    #[test]
+    fn test_set_aspect_ratio() {
+        let camera = |aspect_ratio| {
+            Camera::new(
+                Point3::new(1, 2, 3),
+                Point3::new(0, 0, 0),
+                Vec3::new(0, 1, 0),
+                40.0,
+                aspect_ratio,
+                0.0,
+                2.0,
+            )
+        };
+        let expected = camera(2.0);
+        let mut actual = camera(0.5);
+        actual.set_aspect_ratio(2.0);
+
+        let mut rng = SmallRng::seed_from_u64(0);
+        for (s, t) in [(0.0, 0.0), (1.0, 1.0), (0.25, 0.75)] {
+            let a = expected.get_ray(s, t, &mut rng);
+            let b = actual.get_ray(s, t, &mut rng);
+            assert!((a.origin - b.origin).near_zero());
+            assert!((a.direction - b.direction).length() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_shutter() {
+        let camera = Camera::new(
+            Point3::new(0, 0, 0),
+            Point3::new(0, 0, -1),
+            Vec3::new(0, 1, 0),
+            90.0,
+            1.0,
+            0.0,
+            1.0,
+        )
+        .with_shutter(1.0, 2.0);
+        let mut rng = SmallRng::seed_from_u64(0);
+
+        let times: Vec<f64> = (0..100)
+            .map(|_| camera.get_ray(0.5, 0.5, &mut rng).time)
+            .collect();
+        assert!(times.iter().all(|t| (1.0..2.0).contains(t)));
+        assert!(times.iter().any(|&t| t < 1.5) && times.iter().any(|&t| t > 1.5));
+
+        let camera = camera.with_shutter(3.0, 3.0);
+        assert_eq!(3.0, camera.get_ray(0.5, 0.5, &mut rng).time);
+    }
+}
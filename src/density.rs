@@ -0,0 +1,41 @@
+//! Density fields of heterogeneous participating media
+
+use std::sync::Arc;
+
+use crate::vec3::Point3;
+
+mod grid;
+pub use grid::*;
+mod noise;
+pub use noise::*;
+
+pub trait Density {
+    /// Density at `p`, between zero and `max_density`
+    fn density(&self, p: Point3) -> f64;
+
+    /// Upper bound of the density everywhere, used as the majorant when
+    /// tracking rays through the medium
+    fn max_density(&self) -> f64;
+}
+
+/// The same density everywhere
+impl Density for f64 {
+    fn density(&self, _p: Point3) -> f64 {
+        *self
+    }
+
+    fn max_density(&self) -> f64 {
+        *self
+    }
+}
+
+/// Allows one density field to be shared between many media
+impl<D: Density + ?Sized> Density for Arc<D> {
+    fn density(&self, p: Point3) -> f64 {
+        (**self).density(p)
+    }
+
+    fn max_density(&self) -> f64 {
+        (**self).max_density()
+    }
+}
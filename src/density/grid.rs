@@ -0,0 +1,120 @@
+use crate::{hittable::Aabb, vec3::Point3};
+
+use super::Density;
+
+/// Densities sampled on a regular grid spanning a box, interpolated
+/// trilinearly in between. The density is zero outside of the box.
+#[derive(Debug, Clone)]
+pub struct Grid {
+    bounds: Aabb,
+    resolution: [usize; 3],
+    /// Indexed by `x + nx * (y + ny * z)`
+    values: Vec<f64>,
+    max: f64,
+}
+
+impl Grid {
+    /// Panics if `values` does not hold one value per grid point, or if any
+    /// value is negative
+    pub fn new(bounds: Aabb, resolution: [usize; 3], values: Vec<f64>) -> Self {
+        assert_eq!(
+            resolution.iter().product::<usize>(),
+            values.len(),
+            "grid needs one value per point"
+        );
+        assert!(
+            values.iter().all(|&v| v >= 0.0),
+            "densities must not be negative"
+        );
+        let max = values.iter().copied().fold(0.0, f64::max);
+        Self {
+            bounds,
+            resolution,
+            values,
+            max,
+        }
+    }
+
+    fn value(&self, [x, y, z]: [usize; 3]) -> f64 {
+        let [nx, ny, _] = self.resolution;
+        self.values[x + nx * (y + ny * z)]
+    }
+}
+
+impl Density for Grid {
+    fn density(&self, p: Point3) -> f64 {
+        let (min, max) = (self.bounds.minimum, self.bounds.maximum);
+        let relative = [
+            (p.x() - min.x()) / (max.x() - min.x()),
+            (p.y() - min.y()) / (max.y() - min.y()),
+            (p.z() - min.z()) / (max.z() - min.z()),
+        ];
+        if relative.iter().any(|r| !(0.0..=1.0).contains(r)) {
+            return 0.0;
+        }
+
+        // Grid points sit at the corners of the cells, the first and last
+        // on the faces of the box
+        let mut lower = [0; 3];
+        let mut fraction = [0.0; 3];
+        for axis in 0..3 {
+            let n = self.resolution[axis];
+            if n < 2 {
+                continue;
+            }
+            let x = relative[axis] * (n - 1) as f64;
+            lower[axis] = (x.floor() as usize).min(n - 2);
+            fraction[axis] = x - lower[axis] as f64;
+        }
+
+        // Corners with zero weight are skipped, which also keeps flat axes
+        // from being indexed past their single point
+        let mut accum = 0.0;
+        for corner in 0..8 {
+            let mut index = lower;
+            let mut weight = 1.0;
+            for axis in 0..3 {
+                if corner >> axis & 1 == 1 {
+                    index[axis] += 1;
+                    weight *= fraction[axis];
+                } else {
+                    weight *= 1.0 - fraction[axis];
+                }
+            }
+            if weight > 0.0 {
+                accum += weight * self.value(index);
+            }
+        }
+        accum
+    }
+
+    fn max_density(&self) -> f64 {
+        self.max
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interpolation() {
+        let bounds = Aabb::new(Point3::new(0, 0, 0), Point3::new(2, 1, 1));
+        // Density 1 at x = 0 and 3 at x = 2, for any y and z
+        let grid = Grid::new(bounds, [2, 2, 1], vec![1.0, 3.0, 1.0, 3.0]);
+
+        assert_eq!(3.0, grid.max_density());
+        assert_eq!(1.0, grid.density(Point3::new(0, 0.5, 0.5)));
+        assert_eq!(2.0, grid.density(Point3::new(1, 0.2, 0.7)));
+        assert_eq!(2.5, grid.density(Point3::new(1.5, 1, 0)));
+        assert_eq!(3.0, grid.density(Point3::new(2, 0, 1)));
+        assert_eq!(0.0, grid.density(Point3::new(2.1, 0.5, 0.5)));
+    }
+
+    #[test]
+    #[should_panic(expected = "one value per point")]
+    fn test_wrong_size() {
+        let bounds = Aabb::new(Point3::new(0, 0, 0), Point3::new(1, 1, 1));
+        Grid::new(bounds, [2, 2, 2], vec![1.0; 7]);
+    }
+}
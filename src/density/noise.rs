@@ -0,0 +1,59 @@
+use crate::{texture::Perlin, vec3::Point3};
+
+use super::Density;
+
+const OCTAVES: u32 = 7;
+
+/// Wispy density following Perlin turbulence, like smoke or clouds
+#[derive(Clone)]
+pub struct NoiseDensity {
+    perlin: Perlin,
+    /// Frequency of the noise
+    scale: f64,
+    max_density: f64,
+}
+
+impl NoiseDensity {
+    /// Fields with the same seed look the same
+    pub fn new(seed: u64, max_density: f64) -> Self {
+        Self {
+            perlin: Perlin::new(seed),
+            scale: 1.0,
+            max_density,
+        }
+    }
+
+    pub fn with_scale(mut self, scale: f64) -> Self {
+        self.scale = scale;
+        self
+    }
+}
+
+impl Density for NoiseDensity {
+    fn density(&self, p: Point3) -> f64 {
+        let t = self.perlin.turbulence(self.scale * p, OCTAVES);
+        self.max_density * t.clamp(0.0, 1.0)
+    }
+
+    fn max_density(&self) -> f64 {
+        self.max_density
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bounded() {
+        let a = NoiseDensity::new(3, 2.0).with_scale(4.0);
+        let b = NoiseDensity::new(3, 2.0).with_scale(4.0);
+        for i in 0..200 {
+            let i = i as f64;
+            let p = Point3::new(0.37 * i, -0.21 * i, 0.13 * i);
+            let density = a.density(p);
+            assert!((0.0..=a.max_density()).contains(&density), "{}", density);
+            assert_eq!(density, b.density(p));
+        }
+    }
+}
@@ -0,0 +1,187 @@
+//! Piecewise constant distributions for importance sampling
+
+/// Distribution over `[0, 1)` proportional to a step function
+#[derive(Debug, Clone, PartialEq)]
+pub struct Distribution1D {
+    func: Vec<f64>,
+    /// `cdf[i]` is the probability of sampling before step `i`
+    cdf: Vec<f64>,
+    integral: f64,
+}
+
+impl Distribution1D {
+    /// Panics if `func` is empty or has negative values. If all values are
+    /// zero, the distribution is uniform.
+    pub fn new(func: Vec<f64>) -> Self {
+        assert!(!func.is_empty(), "distribution needs at least one value");
+        assert!(func.iter().all(|&f| f >= 0.0), "negative function value");
+
+        let n = func.len() as f64;
+        let mut cdf = Vec::with_capacity(func.len() + 1);
+        cdf.push(0.0);
+        for f in &func {
+            cdf.push(cdf.last().unwrap() + f / n);
+        }
+        let integral = *cdf.last().unwrap();
+
+        if integral > 0.0 {
+            cdf.iter_mut().for_each(|c| *c /= integral);
+        } else {
+            cdf.iter_mut()
+                .enumerate()
+                .for_each(|(i, c)| *c = i as f64 / n);
+        }
+
+        Self {
+            func,
+            cdf,
+            integral,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.func.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.func.is_empty()
+    }
+
+    /// Integral of the step function over `[0, 1)`
+    pub fn integral(&self) -> f64 {
+        self.integral
+    }
+
+    /// Map a uniform `u` in `[0, 1)` to a step index and its probability
+    pub fn sample_discrete(&self, u: f64) -> (usize, f64) {
+        let index = self.find(u);
+        (index, self.cdf[index + 1] - self.cdf[index])
+    }
+
+    /// Map a uniform `u` in `[0, 1)` to a value `x` in `[0, 1)`, returning
+    /// `x`, its density and the step it falls into
+    pub fn sample_continuous(&self, u: f64) -> (f64, f64, usize) {
+        let index = self.find(u);
+        let width = self.cdf[index + 1] - self.cdf[index];
+        let offset = if width > 0.0 {
+            (u - self.cdf[index]) / width
+        } else {
+            0.0
+        };
+        let x = ((index as f64 + offset) / self.len() as f64).min(1.0 - f64::EPSILON);
+        (x, self.pdf(x), index)
+    }
+
+    /// Density of `sample_continuous` returning `x`
+    pub fn pdf(&self, x: f64) -> f64 {
+        let index = ((x * self.len() as f64) as usize).min(self.len() - 1);
+        if self.integral > 0.0 {
+            self.func[index] / self.integral
+        } else {
+            1.0
+        }
+    }
+
+    /// Probability of `sample_discrete` returning `index`
+    pub fn discrete_pdf(&self, index: usize) -> f64 {
+        self.cdf[index + 1] - self.cdf[index]
+    }
+
+    /// Last step whose cdf is at most `u`, skipping empty steps
+    fn find(&self, u: f64) -> usize {
+        let index = self.cdf.partition_point(|&c| c <= u).saturating_sub(1);
+        index.min(self.len() - 1)
+    }
+}
+
+/// Distribution over `[0, 1)²` proportional to a function that is constant
+/// on each cell of a grid
+#[derive(Debug, Clone, PartialEq)]
+pub struct Distribution2D {
+    /// One distribution over `u` per row
+    conditional: Vec<Distribution1D>,
+    /// Distribution over `v`, proportional to the integral of each row
+    marginal: Distribution1D,
+}
+
+impl Distribution2D {
+    /// `func` holds `height` rows of `width` values each
+    pub fn new(func: &[f64], width: usize, height: usize) -> Self {
+        assert_eq!(width * height, func.len());
+
+        let conditional: Vec<Distribution1D> = func
+            .chunks(width)
+            .map(|row| Distribution1D::new(row.to_vec()))
+            .collect();
+        let marginal = Distribution1D::new(conditional.iter().map(|d| d.integral()).collect());
+
+        Self {
+            conditional,
+            marginal,
+        }
+    }
+
+    /// Map two uniform values in `[0, 1)` to a point `(u, v)` in `[0, 1)²`
+    /// and its density
+    pub fn sample(&self, u1: f64, u2: f64) -> ((f64, f64), f64) {
+        let (v, pdf_v, row) = self.marginal.sample_continuous(u2);
+        let (u, pdf_u, _) = self.conditional[row].sample_continuous(u1);
+        ((u, v), pdf_u * pdf_v)
+    }
+
+    /// Density of `sample` returning `(u, v)`
+    pub fn pdf(&self, (u, v): (f64, f64)) -> f64 {
+        let row = ((v * self.marginal.len() as f64) as usize).min(self.marginal.len() - 1);
+        self.marginal.pdf(v) * self.conditional[row].pdf(u)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_distribution_1d() {
+        let distr = Distribution1D::new(vec![1.0, 0.0, 3.0]);
+        assert!((distr.integral() - 4.0 / 3.0).abs() < 1e-12);
+
+        assert_eq!((0, 0.25), distr.sample_discrete(0.1));
+        assert_eq!((2, 0.75), distr.sample_discrete(0.25));
+        assert_eq!((2, 0.75), distr.sample_discrete(0.99));
+
+        let (x, pdf, index) = distr.sample_continuous(0.125);
+        assert!((x - 1.0 / 6.0).abs() < 1e-12);
+        assert_eq!(0.75, pdf);
+        assert_eq!(0, index);
+
+        let (x, pdf, index) = distr.sample_continuous(0.625);
+        assert!((x - 5.0 / 6.0).abs() < 1e-12);
+        assert_eq!(2.25, pdf);
+        assert_eq!(2, index);
+
+        assert_eq!(0.0, distr.pdf(0.5));
+    }
+
+    #[test]
+    fn test_distribution_1d_zero() {
+        let distr = Distribution1D::new(vec![0.0; 4]);
+        assert_eq!((2, 0.25), distr.sample_discrete(0.6));
+        assert_eq!(1.0, distr.pdf(0.3));
+    }
+
+    #[test]
+    fn test_distribution_2d() {
+        #[rustfmt::skip]
+        let distr = Distribution2D::new(&[
+            0.0, 0.0,
+            1.0, 3.0,
+        ], 2, 2);
+
+        let ((u, v), pdf) = distr.sample(0.5, 0.5);
+        assert!(u >= 0.5 && v >= 0.5);
+        assert_eq!(3.0, pdf);
+        assert_eq!(pdf, distr.pdf((u, v)));
+        assert_eq!(0.0, distr.pdf((0.25, 0.25)));
+        assert_eq!(1.0, distr.pdf((0.25, 0.75)));
+    }
+}
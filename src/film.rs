@@ -0,0 +1,165 @@
+//! Framebuffer that accumulates samples in linear space
+
+use crate::vec3::Color;
+
+mod tonemap;
+pub use tonemap::*;
+
+/// Accumulates radiance samples per pixel.
+///
+/// Pixels are addressed with (0, 0) at the top left. Sums are kept in linear
+/// space, so films can be merged and resolved at any time.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Film {
+    width: u32,
+    height: u32,
+    sums: Vec<Color>,
+    counts: Vec<u32>,
+}
+
+impl Film {
+    pub fn new(width: u32, height: u32) -> Self {
+        let len = width as usize * height as usize;
+        Self {
+            width,
+            height,
+            sums: vec![Color::default(); len],
+            counts: vec![0; len],
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    fn index(&self, x: u32, y: u32) -> usize {
+        assert!(x < self.width && y < self.height, "pixel out of bounds");
+        y as usize * self.width as usize + x as usize
+    }
+
+    pub fn add_sample(&mut self, x: u32, y: u32, color: Color) {
+        self.add_samples(x, y, color, 1);
+    }
+
+    /// Add `count` samples whose radiance adds up to `sum`
+    pub fn add_samples(&mut self, x: u32, y: u32, sum: Color, count: u32) {
+        let i = self.index(x, y);
+        self.sums[i] += sum;
+        self.counts[i] += count;
+    }
+
+    /// Add all samples of `other`, which must have the same size
+    pub fn merge(&mut self, other: &Film) {
+        assert_eq!(
+            (self.width, self.height),
+            (other.width, other.height),
+            "films must have the same size"
+        );
+        for (sum, other) in self.sums.iter_mut().zip(&other.sums) {
+            *sum += *other;
+        }
+        for (count, other) in self.counts.iter_mut().zip(&other.counts) {
+            *count += other;
+        }
+    }
+
+    pub fn sample_count(&self, x: u32, y: u32) -> u32 {
+        self.counts[self.index(x, y)]
+    }
+
+    /// Average radiance of a pixel, black if it has no samples yet
+    pub fn pixel(&self, x: u32, y: u32) -> Color {
+        let i = self.index(x, y);
+        average(self.sums[i], self.counts[i])
+    }
+
+    /// The average linear radiance of every pixel
+    pub fn image(&self) -> Image {
+        Image {
+            width: self.width,
+            height: self.height,
+            pixels: self
+                .sums
+                .iter()
+                .zip(&self.counts)
+                .map(|(&sum, &count)| average(sum, count))
+                .collect(),
+        }
+    }
+}
+
+fn average(sum: Color, count: u32) -> Color {
+    if count == 0 {
+        Color::default()
+    } else {
+        sum / count as f64
+    }
+}
+
+/// An image with linear colors, stored row by row from the top left
+#[derive(Debug, Clone, PartialEq)]
+pub struct Image {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<Color>,
+}
+
+impl Image {
+    /// Apply a per-pixel operation, such as a tone mapper
+    pub fn map(mut self, f: impl Fn(Color) -> Color) -> Self {
+        self.pixels.iter_mut().for_each(|pixel| *pixel = f(*pixel));
+        self
+    }
+
+    pub fn tone_map(self, tone_mapper: &impl ToneMap) -> Self {
+        self.map(|color| tone_mapper.tone_map(color))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_accumulate() {
+        let mut film = Film::new(2, 1);
+        film.add_sample(1, 0, Color::new(1, 2, 3));
+        film.add_sample(1, 0, Color::new(3, 2, 1));
+        film.add_samples(0, 0, Color::new(4, 4, 4), 4);
+
+        assert_eq!(2, film.sample_count(1, 0));
+        assert_eq!(Color::new(2, 2, 2), film.pixel(1, 0));
+        assert_eq!(
+            vec![Color::new(1, 1, 1), Color::new(2, 2, 2)],
+            film.image().pixels
+        );
+    }
+
+    #[test]
+    fn test_empty_pixel() {
+        let film = Film::new(1, 1);
+        assert_eq!(Color::new(0, 0, 0), film.pixel(0, 0));
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut a = Film::new(1, 1);
+        a.add_sample(0, 0, Color::new(1, 1, 1));
+        let mut b = Film::new(1, 1);
+        b.add_samples(0, 0, Color::new(5, 5, 5), 3);
+
+        a.merge(&b);
+        assert_eq!(4, a.sample_count(0, 0));
+        assert_eq!(Color::new(1.5, 1.5, 1.5), a.pixel(0, 0));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_out_of_bounds() {
+        Film::new(2, 2).add_sample(2, 0, Color::new(1, 1, 1));
+    }
+}
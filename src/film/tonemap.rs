@@ -0,0 +1,93 @@
+use crate::vec3::{Color, Vec3};
+
+/// Maps linear radiance to the displayable range [0, 1]
+pub trait ToneMap {
+    fn tone_map(&self, color: Color) -> Color;
+}
+
+/// Scale radiance by 2^stops before tone mapping with `T`
+pub struct Exposure<T: ToneMap> {
+    pub stops: f64,
+    pub tone_map: T,
+}
+
+impl<T: ToneMap> ToneMap for Exposure<T> {
+    fn tone_map(&self, color: Color) -> Color {
+        self.tone_map.tone_map(color * 2f64.powf(self.stops))
+    }
+}
+
+/// Clip everything above 1.0
+pub struct Clamp;
+
+impl ToneMap for Clamp {
+    fn tone_map(&self, color: Color) -> Color {
+        let Vec3(r, g, b) = color;
+        Vec3(r.clamp(0.0, 1.0), g.clamp(0.0, 1.0), b.clamp(0.0, 1.0))
+    }
+}
+
+/// Reinhard's global operator, applied to luminance to preserve hue
+pub struct Reinhard;
+
+impl ToneMap for Reinhard {
+    fn tone_map(&self, color: Color) -> Color {
+        let l = color.luminance();
+        if l <= 0.0 {
+            return Color::default();
+        }
+        color * (1.0 / (1.0 + l))
+    }
+}
+
+/// Narkowicz' fit of the ACES filmic curve
+pub struct Aces;
+
+impl ToneMap for Aces {
+    fn tone_map(&self, color: Color) -> Color {
+        let curve = |x: f64| {
+            let x = x.max(0.0);
+            ((x * (2.51 * x + 0.03)) / (x * (2.43 * x + 0.59) + 0.14)).clamp(0.0, 1.0)
+        };
+        let Vec3(r, g, b) = color;
+        Vec3(curve(r), curve(g), curve(b))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clamp() {
+        assert_eq!(
+            Color::new(0, 0.5, 1),
+            Clamp.tone_map(Color::new(-1, 0.5, 7))
+        );
+    }
+
+    #[test]
+    fn test_reinhard() {
+        assert_eq!(
+            Color::new(0.5, 0.5, 0.5),
+            Reinhard.tone_map(Color::new(1, 1, 1))
+        );
+        let bright = Reinhard.tone_map(Color::new(1000, 1000, 1000));
+        assert!(bright.x() < 1.0 && bright.x() > 0.99);
+    }
+
+    #[test]
+    fn test_exposure() {
+        let tm = Exposure {
+            stops: 1.0,
+            tone_map: Clamp,
+        };
+        assert_eq!(Color::new(0.5, 1, 1), tm.tone_map(Color::new(0.25, 0.5, 1)));
+    }
+
+    #[test]
+    fn test_aces() {
+        assert_eq!(Color::new(0, 0, 0), Aces.tone_map(Color::new(0, 0, 0)));
+        assert_eq!(1.0, Aces.tone_map(Color::new(100, 100, 100)).x());
+    }
+}
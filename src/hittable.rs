@@ -1,21 +1,62 @@
+use std::sync::Arc;
+
+use rand::{prelude::SmallRng, Rng};
+
 use crate::{
     material::Material,
     ray::Ray,
     vec3::{Point3, Vec3},
 };
 
+mod aabb;
+pub use aabb::*;
+mod bvh;
+pub use bvh::*;
+mod capsule;
+pub use capsule::*;
+mod cone;
+pub use cone::*;
+mod constant_medium;
+pub use constant_medium::*;
+mod cylinder;
+pub use cylinder::*;
+mod disk;
+pub use disk::*;
+mod frame;
+mod heterogeneous_medium;
+pub use heterogeneous_medium::*;
+mod mesh;
+pub use mesh::*;
+mod plane;
+pub use plane::*;
+mod quad;
+pub use quad::*;
 mod sphere;
 pub use sphere::*;
+mod torus;
+pub use torus::*;
+mod transformed;
+pub use transformed::*;
+mod triangle;
+pub use triangle::*;
 
-pub struct HitRecord {
+pub struct HitRecord<'a> {
     pub p: Point3,
+    /// Shading normal, always facing against the incident ray
     pub normal: Vec3,
-    pub material: Box<dyn Material>,
+    /// Normal of the actual surface, facing the same side as `normal`
+    pub geometric_normal: Vec3,
+    pub material: &'a dyn Material,
     pub t: f64,
     pub front_face: bool,
+    /// Barycentric coordinates of the hit point relative to the second and
+    /// third vertex of a triangle. Zero for other shapes.
+    pub barycentric: (f64, f64),
+    /// Surface coordinates for texture lookups
+    pub uv: (f64, f64),
 }
 
-impl HitRecord {
+impl HitRecord<'_> {
     pub fn set_face_normal(&mut self, ray: Ray, outward_normal: Vec3) {
         self.front_face = ray.direction.dot(&outward_normal) < 0.0;
         self.normal = if self.front_face {
@@ -23,22 +64,59 @@ impl HitRecord {
         } else {
             -outward_normal
         };
+        self.geometric_normal = self.normal;
     }
 }
 
 pub trait Hittable {
-    fn hit(&self, ray: Ray, t_min: f64, t_max: f64) -> Option<HitRecord>;
+    fn hit(&self, ray: Ray, t_min: f64, t_max: f64, rng: &mut SmallRng) -> Option<HitRecord<'_>>;
+
+    /// Box enclosing the object, or `None` if it is unbounded.
+    fn bounding_box(&self) -> Option<Aabb>;
+
+    /// Density, per unit of solid angle as seen from `origin`, with which
+    /// `sample_direction` chooses `direction`. Zero for objects that cannot
+    /// be sampled.
+    fn pdf_value(&self, _origin: Point3, _direction: Vec3, _rng: &mut SmallRng) -> f64 {
+        0.0
+    }
+
+    /// Direction from `origin` towards a random point of the object, for
+    /// sampling lights directly. `None` for objects that cannot be sampled.
+    fn sample_direction(&self, _origin: Point3, _rng: &mut SmallRng) -> Option<Vec3> {
+        None
+    }
+}
+
+/// Allows one object to be shared, e.g. between several `Transformed`
+/// instances
+impl<H: Hittable + ?Sized> Hittable for Arc<H> {
+    fn hit(&self, ray: Ray, t_min: f64, t_max: f64, rng: &mut SmallRng) -> Option<HitRecord<'_>> {
+        (**self).hit(ray, t_min, t_max, rng)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        (**self).bounding_box()
+    }
+
+    fn pdf_value(&self, origin: Point3, direction: Vec3, rng: &mut SmallRng) -> f64 {
+        (**self).pdf_value(origin, direction, rng)
+    }
+
+    fn sample_direction(&self, origin: Point3, rng: &mut SmallRng) -> Option<Vec3> {
+        (**self).sample_direction(origin, rng)
+    }
 }
 
-pub type HittableList = Vec<Box<dyn Hittable + Sync>>;
+pub type HittableList = Vec<Box<dyn Hittable + Send + Sync>>;
 
 impl Hittable for HittableList {
-    fn hit(&self, ray: Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+    fn hit(&self, ray: Ray, t_min: f64, t_max: f64, rng: &mut SmallRng) -> Option<HitRecord<'_>> {
         let mut hit = None;
         let mut closest_so_far = t_max;
 
         for hittable in self.iter() {
-            if let Some(rec) = hittable.hit(ray, t_min, closest_so_far) {
+            if let Some(rec) = hittable.hit(ray, t_min, closest_so_far, rng) {
                 closest_so_far = rec.t;
                 hit = Some(rec);
             }
@@ -46,4 +124,34 @@ impl Hittable for HittableList {
 
         hit
     }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        if self.is_empty() {
+            return None;
+        }
+
+        self.iter().try_fold(Aabb::empty(), |acc, hittable| {
+            hittable.bounding_box().map(|bbox| acc.surrounding(&bbox))
+        })
+    }
+
+    /// Sampling a list picks one of the objects evenly, so it can be used
+    /// as the list of lights of a scene
+    fn pdf_value(&self, origin: Point3, direction: Vec3, rng: &mut SmallRng) -> f64 {
+        if self.is_empty() {
+            return 0.0;
+        }
+        let sum: f64 = self
+            .iter()
+            .map(|hittable| hittable.pdf_value(origin, direction, rng))
+            .sum();
+        sum / self.len() as f64
+    }
+
+    fn sample_direction(&self, origin: Point3, rng: &mut SmallRng) -> Option<Vec3> {
+        if self.is_empty() {
+            return None;
+        }
+        self[rng.gen_range(0..self.len())].sample_direction(origin, rng)
+    }
 }
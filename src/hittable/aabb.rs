@@ -0,0 +1,138 @@
+use crate::{ray::Ray, vec3::Point3};
+
+/// Axis-aligned bounding box
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Aabb {
+    pub minimum: Point3,
+    pub maximum: Point3,
+}
+
+impl Aabb {
+    pub fn new(minimum: Point3, maximum: Point3) -> Self {
+        Self { minimum, maximum }
+    }
+
+    /// A box that contains nothing. It is the identity for `surrounding`.
+    pub fn empty() -> Self {
+        Self {
+            minimum: Point3::new(f64::INFINITY, f64::INFINITY, f64::INFINITY),
+            maximum: Point3::new(f64::NEG_INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY),
+        }
+    }
+
+    /// A box that contains everything, used for objects without bounds.
+    pub fn infinite() -> Self {
+        Self {
+            minimum: Point3::new(f64::NEG_INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY),
+            maximum: Point3::new(f64::INFINITY, f64::INFINITY, f64::INFINITY),
+        }
+    }
+
+    /// Smallest box containing both `self` and `other`
+    pub fn surrounding(&self, other: &Self) -> Self {
+        Self {
+            minimum: Point3::new(
+                self.minimum.x().min(other.minimum.x()),
+                self.minimum.y().min(other.minimum.y()),
+                self.minimum.z().min(other.minimum.z()),
+            ),
+            maximum: Point3::new(
+                self.maximum.x().max(other.maximum.x()),
+                self.maximum.y().max(other.maximum.y()),
+                self.maximum.z().max(other.maximum.z()),
+            ),
+        }
+    }
+
+    pub fn centroid(&self) -> Point3 {
+        0.5 * (self.minimum + self.maximum)
+    }
+
+    /// Surface area of the box, as used by the surface area heuristic
+    pub fn surface_area(&self) -> f64 {
+        let d = self.maximum - self.minimum;
+        if d.x() < 0.0 || d.y() < 0.0 || d.z() < 0.0 {
+            return 0.0;
+        }
+        2.0 * (d.x() * d.y() + d.y() * d.z() + d.z() * d.x())
+    }
+
+    /// Slab test: whether `ray` passes through the box within [t_min, t_max]
+    pub fn hit(&self, ray: Ray, mut t_min: f64, mut t_max: f64) -> bool {
+        let origin = [ray.origin.x(), ray.origin.y(), ray.origin.z()];
+        let direction = [ray.direction.x(), ray.direction.y(), ray.direction.z()];
+        let minimum = [self.minimum.x(), self.minimum.y(), self.minimum.z()];
+        let maximum = [self.maximum.x(), self.maximum.y(), self.maximum.z()];
+
+        for a in 0..3 {
+            let inv_d = 1.0 / direction[a];
+            let mut t0 = (minimum[a] - origin[a]) * inv_d;
+            let mut t1 = (maximum[a] - origin[a]) * inv_d;
+            if inv_d < 0.0 {
+                std::mem::swap(&mut t0, &mut t1);
+            }
+            // `max`/`min` ignore NaN, which occurs when the ray lies exactly
+            // on a slab boundary.
+            t_min = t_min.max(t0);
+            t_max = t_max.min(t1);
+            if t_max < t_min {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vec3::Vec3;
+
+    fn unit_box() -> Aabb {
+        Aabb::new(Point3::new(-1, -1, -1), Point3::new(1, 1, 1))
+    }
+
+    #[test]
+    fn test_hit() {
+        let ray = Ray {
+            origin: Point3::new(0, 0, -5),
+            direction: Vec3::new(0, 0, 1),
+            time: 0.0,
+        };
+        assert!(unit_box().hit(ray, 0.0, f64::INFINITY));
+        assert!(!unit_box().hit(ray, 0.0, 3.0));
+        assert!(!unit_box().hit(ray, 7.0, f64::INFINITY));
+
+        let ray = Ray {
+            origin: Point3::new(0, 2, -5),
+            direction: Vec3::new(0, 0, 1),
+            time: 0.0,
+        };
+        assert!(!unit_box().hit(ray, 0.0, f64::INFINITY));
+
+        let ray = Ray {
+            origin: Point3::new(0, 0, 5),
+            direction: Vec3::new(0, 0, -1),
+            time: 0.0,
+        };
+        assert!(unit_box().hit(ray, 0.0, f64::INFINITY));
+    }
+
+    #[test]
+    fn test_surrounding() {
+        let a = Aabb::new(Point3::new(0, 0, 0), Point3::new(1, 1, 1));
+        let b = Aabb::new(Point3::new(-1, 0.5, 0), Point3::new(0.5, 2, 1));
+        assert_eq!(
+            Aabb::new(Point3::new(-1, 0, 0), Point3::new(1, 2, 1)),
+            a.surrounding(&b)
+        );
+        assert_eq!(a, Aabb::empty().surrounding(&a));
+    }
+
+    #[test]
+    fn test_surface_area() {
+        assert_eq!(24.0, unit_box().surface_area());
+        assert_eq!(0.0, Aabb::empty().surface_area());
+    }
+}
@@ -0,0 +1,260 @@
+use rand::prelude::SmallRng;
+
+use crate::ray::Ray;
+
+use super::{Aabb, HitRecord, Hittable, HittableList};
+
+/// Relative cost of traversing one node, compared to intersecting a primitive
+const TRAVERSAL_COST: f64 = 0.125;
+/// Nodes with more primitives than this are always split
+const MAX_LEAF_SIZE: usize = 4;
+
+/// Bounding volume hierarchy over a `HittableList`.
+///
+/// The tree is split using the surface area heuristic. Objects that do not
+/// have a bounding box (e.g. infinite planes) are kept in a separate list
+/// next to the tree and tested on every ray.
+pub struct BvhNode {
+    bbox: Aabb,
+    node: Node,
+}
+
+enum Node {
+    Leaf(HittableList),
+    Branch(Box<BvhNode>, Box<BvhNode>),
+}
+
+impl BvhNode {
+    pub fn new(objects: HittableList) -> Self {
+        let (bounded, unbounded): (Vec<_>, Vec<_>) = objects
+            .into_iter()
+            .map(|object| (object.bounding_box(), object))
+            .partition(|(bbox, _)| bbox.is_some());
+
+        let bounded = bounded
+            .into_iter()
+            .map(|(bbox, object)| (bbox.unwrap(), object))
+            .collect();
+        let tree = Self::build(bounded);
+
+        if unbounded.is_empty() {
+            return tree;
+        }
+
+        let unbounded = Self {
+            bbox: Aabb::infinite(),
+            node: Node::Leaf(unbounded.into_iter().map(|(_, o)| o).collect()),
+        };
+        Self {
+            bbox: Aabb::infinite(),
+            node: Node::Branch(Box::new(tree), Box::new(unbounded)),
+        }
+    }
+
+    fn build(mut items: Vec<(Aabb, Box<dyn Hittable + Send + Sync>)>) -> Self {
+        let bbox = items
+            .iter()
+            .fold(Aabb::empty(), |acc, (bbox, _)| acc.surrounding(bbox));
+        let n = items.len();
+
+        if n <= 1 {
+            return Self::leaf(bbox, items);
+        }
+
+        // Find the cheapest split along any axis. Primitives are ordered by
+        // the centroid of their bounding box, and every split position is
+        // evaluated by sweeping from both ends.
+        let mut best: Option<(usize, usize, f64)> = None;
+        for axis in 0..3 {
+            sort_by_axis(&mut items, axis);
+
+            let mut right_areas = vec![0.0; n];
+            let mut acc = Aabb::empty();
+            for i in (1..n).rev() {
+                acc = acc.surrounding(&items[i].0);
+                right_areas[i] = acc.surface_area();
+            }
+
+            let mut acc = Aabb::empty();
+            for i in 1..n {
+                acc = acc.surrounding(&items[i - 1].0);
+                let cost = acc.surface_area() * i as f64 + right_areas[i] * (n - i) as f64;
+                if !matches!(best, Some((_, _, c)) if c <= cost) {
+                    best = Some((axis, i, cost));
+                }
+            }
+        }
+
+        let (axis, split, cost) = best.unwrap();
+        let area = bbox.surface_area();
+        let split_cost = if area > 0.0 {
+            TRAVERSAL_COST + cost / area
+        } else {
+            TRAVERSAL_COST + n as f64
+        };
+        if n <= MAX_LEAF_SIZE && split_cost >= n as f64 {
+            return Self::leaf(bbox, items);
+        }
+
+        sort_by_axis(&mut items, axis);
+        let right = items.split_off(split);
+        Self {
+            bbox,
+            node: Node::Branch(Box::new(Self::build(items)), Box::new(Self::build(right))),
+        }
+    }
+
+    fn leaf(bbox: Aabb, items: Vec<(Aabb, Box<dyn Hittable + Send + Sync>)>) -> Self {
+        Self {
+            bbox,
+            node: Node::Leaf(items.into_iter().map(|(_, object)| object).collect()),
+        }
+    }
+}
+
+fn sort_by_axis(items: &mut [(Aabb, Box<dyn Hittable + Send + Sync>)], axis: usize) {
+    let key = |bbox: &Aabb| {
+        let c = bbox.centroid();
+        [c.x(), c.y(), c.z()][axis]
+    };
+    items.sort_by(|(a, _), (b, _)| key(a).total_cmp(&key(b)));
+}
+
+impl Hittable for BvhNode {
+    fn hit(&self, ray: Ray, t_min: f64, t_max: f64, rng: &mut SmallRng) -> Option<HitRecord<'_>> {
+        if !self.bbox.hit(ray, t_min, t_max) {
+            return None;
+        }
+
+        match &self.node {
+            Node::Leaf(objects) => objects.hit(ray, t_min, t_max, rng),
+            Node::Branch(left, right) => {
+                let left_hit = left.hit(ray, t_min, t_max, rng);
+                let closest_so_far = left_hit.as_ref().map_or(t_max, |rec| rec.t);
+                right.hit(ray, t_min, closest_so_far, rng).or(left_hit)
+            }
+        }
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(self.bbox).filter(|bbox| *bbox != Aabb::infinite())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{prelude::SmallRng, Rng, SeedableRng};
+
+    use super::*;
+    use crate::{
+        hittable::{MovingSphere, Sphere},
+        material::Lambertian,
+        vec3::{Color, Point3, Vec3},
+    };
+
+    fn random_spheres(rng: &mut SmallRng, count: usize) -> HittableList {
+        (0..count)
+            .map(|_| {
+                let center = 10.0 * Vec3::new_random_range(rng, -1.0, 1.0);
+                let radius = rng.gen_range(0.1..1.0);
+                Box::new(Sphere::new(
+                    center,
+                    radius,
+                    Lambertian::new(Color::new(0.5, 0.5, 0.5)),
+                )) as Box<dyn Hittable + Send + Sync>
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_matches_list() {
+        let mut rng = SmallRng::seed_from_u64(42);
+        let list = random_spheres(&mut rng, 200);
+        let bvh = BvhNode::new(random_spheres(&mut SmallRng::seed_from_u64(42), 200));
+
+        for _ in 0..1000 {
+            let ray = Ray {
+                origin: 20.0 * Vec3::new_random_range(&mut rng, -1.0, 1.0),
+                direction: Vec3::new_random_range(&mut rng, -1.0, 1.0),
+                time: 0.0,
+            };
+            let expected = list
+                .hit(ray, 0.001, f64::INFINITY, &mut rng)
+                .map(|rec| rec.t);
+            let actual = bvh
+                .hit(ray, 0.001, f64::INFINITY, &mut rng)
+                .map(|rec| rec.t);
+            assert_eq!(expected, actual);
+        }
+    }
+
+    #[test]
+    fn test_bounding_box() {
+        let list: HittableList = vec![
+            Box::new(Sphere::new(
+                Point3::new(0, 0, 0),
+                1.0,
+                Lambertian::new(Color::new(1, 1, 1)),
+            )),
+            Box::new(Sphere::new(
+                Point3::new(5, 0, 0),
+                2.0,
+                Lambertian::new(Color::new(1, 1, 1)),
+            )),
+        ];
+        let bvh = BvhNode::new(list);
+        assert_eq!(
+            Some(Aabb::new(Point3::new(-1, -2, -2), Point3::new(7, 2, 2))),
+            bvh.bounding_box()
+        );
+    }
+
+    #[test]
+    fn test_moving_outside_shutter() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let world = || -> HittableList {
+            vec![
+                Box::new(MovingSphere::new(
+                    Point3::new(0, 0, 0),
+                    Point3::new(2, 0, 0),
+                    0.5,
+                    1.0,
+                    0.5,
+                    Lambertian::new(Color::new(1, 1, 1)),
+                )),
+                Box::new(Sphere::new(
+                    Point3::new(0, 10, 0),
+                    1.0,
+                    Lambertian::new(Color::new(1, 1, 1)),
+                )),
+            ]
+        };
+        let list = world();
+        let bvh = BvhNode::new(world());
+
+        // Before `time0` and after `time1`, the sphere is still in its box
+        for (x, time) in [(0, 0.0), (2, 2.0)] {
+            let ray = Ray {
+                origin: Point3::new(x, 0, 5),
+                direction: Vec3::new(0, 0, -1),
+                time,
+            };
+            let expected = list.hit(ray, 0.001, f64::INFINITY, &mut rng).unwrap().t;
+            let actual = bvh.hit(ray, 0.001, f64::INFINITY, &mut rng).unwrap().t;
+            assert_eq!(4.5, expected);
+            assert_eq!(expected, actual);
+        }
+    }
+
+    #[test]
+    fn test_empty() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let bvh = BvhNode::new(vec![]);
+        let ray = Ray {
+            origin: Point3::new(0, 0, 0),
+            direction: Vec3::new(1, 0, 0),
+            time: 0.0,
+        };
+        assert!(bvh.hit(ray, 0.0, f64::INFINITY, &mut rng).is_none());
+    }
+}
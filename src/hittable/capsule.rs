@@ -0,0 +1,204 @@
+use rand::prelude::SmallRng;
+
+use crate::{
+    hittable::HitRecord,
+    material::Material,
+    ray::Ray,
+    vec3::{Point3, Vec3},
+};
+
+use super::{
+    frame::{solve_quadratic, turn, Frame, Nearest},
+    Aabb, Hittable,
+};
+
+/// Points within `radius` of the segment from `a` to `b`: a cylinder closed
+/// by two hemispheres.
+///
+/// `u` goes around the axis and `v` from the end at `a` to the end at `b`.
+pub struct Capsule<M: Material> {
+    frame: Frame,
+    a: Point3,
+    b: Point3,
+    height: f64,
+    radius: f64,
+    material: M,
+}
+
+impl<M: Material> Capsule<M> {
+    /// `a` and `b` must differ
+    pub fn new(a: Point3, b: Point3, radius: f64, material: M) -> Self {
+        let axis = b - a;
+        Self {
+            frame: Frame::new(a, axis),
+            a,
+            b,
+            height: axis.length(),
+            radius,
+            material,
+        }
+    }
+}
+
+impl<M: Material> Hittable for Capsule<M> {
+    fn hit(&self, ray: Ray, t_min: f64, t_max: f64, _rng: &mut SmallRng) -> Option<HitRecord<'_>> {
+        let (o, d) = self.frame.local_ray(ray);
+        let (h, r) = (self.height, self.radius);
+        let v = |z: f64| (z + r) / (h + 2.0 * r);
+        let mut nearest = Nearest::new(t_min, t_max);
+
+        // Cylindrical body, unless the ray runs along the axis
+        let a = d.x() * d.x() + d.y() * d.y();
+        if a > 0.0 {
+            let half_b = o.x() * d.x() + o.y() * d.y();
+            let c = o.x() * o.x() + o.y() * o.y() - r * r;
+            if let Some((t0, t1)) = solve_quadratic(a, half_b, c) {
+                for t in [t0, t1] {
+                    let p = o + t * d;
+                    if (0.0..=h).contains(&p.z()) {
+                        let normal = Vec3::new(p.x() / r, p.y() / r, 0);
+                        nearest.offer((t, normal, (turn(p.x(), p.y()), v(p.z()))));
+                    }
+                }
+            }
+        }
+
+        // Hemispheres beyond each end
+        for (z, outside) in [(0.0, -1.0), (h, 1.0)] {
+            let center = Point3::new(0, 0, z);
+            let oc = o - center;
+            let a = d.length_squared();
+            let half_b = oc.dot(&d);
+            let c = oc.length_squared() - r * r;
+            if let Some((t0, t1)) = solve_quadratic(a, half_b, c) {
+                for t in [t0, t1] {
+                    let p = o + t * d;
+                    if outside * (p.z() - z) >= 0.0 {
+                        let normal = (p - center) / r;
+                        nearest.offer((t, normal, (turn(p.x(), p.y()), v(p.z()))));
+                    }
+                }
+            }
+        }
+
+        nearest
+            .hit
+            .map(|hit| self.frame.hit_record(ray, hit, &self.material))
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let r = Vec3::new(self.radius, self.radius, self.radius);
+        let a = Aabb::new(self.a - r, self.a + r);
+        let b = Aabb::new(self.b - r, self.b + r);
+        Some(a.surrounding(&b))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::{material::Lambertian, vec3::Color};
+
+    fn capsule() -> Capsule<Lambertian> {
+        Capsule::new(
+            Point3::new(-1, 0, 0),
+            Point3::new(1, 0, 0),
+            0.5,
+            Lambertian::new(Color::new(0.5, 0.5, 0.5)),
+        )
+    }
+
+    fn ray(origin: Point3, direction: Vec3) -> Ray {
+        Ray {
+            origin,
+            direction,
+            time: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_body_and_ends() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let capsule = capsule();
+
+        let hit = capsule
+            .hit(
+                ray(Point3::new(0, 3, 0), Vec3::new(0, -1, 0)),
+                0.0,
+                f64::INFINITY,
+                &mut rng,
+            )
+            .unwrap();
+        assert!((hit.t - 2.5).abs() < 1e-12);
+        assert!((hit.normal - Vec3::new(0, 1, 0)).near_zero());
+        assert!((hit.uv.1 - 0.5).abs() < 1e-12);
+
+        let hit = capsule
+            .hit(
+                ray(Point3::new(5, 0, 0), Vec3::new(-1, 0, 0)),
+                0.0,
+                f64::INFINITY,
+                &mut rng,
+            )
+            .unwrap();
+        assert!((hit.t - 3.5).abs() < 1e-12);
+        assert!((hit.normal - Vec3::new(1, 0, 0)).near_zero());
+        assert!((hit.uv.1 - 1.0).abs() < 1e-12);
+
+        // Round end, off the axis
+        let hit = capsule
+            .hit(
+                ray(Point3::new(-1.3, 3, 0), Vec3::new(0, -1, 0)),
+                0.0,
+                f64::INFINITY,
+                &mut rng,
+            )
+            .unwrap();
+        assert!((hit.p - Point3::new(-1.3, 0.4, 0)).length() < 1e-12);
+        assert!((hit.normal - Vec3::new(-0.6, 0.8, 0)).near_zero());
+
+        let bbox = capsule.bounding_box().unwrap();
+        assert_eq!(Point3::new(-1.5, -0.5, -0.5), bbox.minimum);
+        assert_eq!(Point3::new(1.5, 0.5, 0.5), bbox.maximum);
+    }
+
+    #[test]
+    fn test_grazing() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let capsule = capsule();
+        for x in [0.0, 1.2] {
+            let mut graze = |z: f64| {
+                capsule.hit(
+                    ray(Point3::new(x, 3, z), Vec3::new(0, -1, 0)),
+                    0.0,
+                    f64::INFINITY,
+                    &mut rng,
+                )
+            };
+            let edge = (0.25 - (x - 1.0f64).max(0.0).powi(2)).sqrt();
+            assert!(graze(edge - 1e-9).is_some(), "{}", x);
+            assert!(graze(edge + 1e-9).is_none(), "{}", x);
+        }
+    }
+
+    #[test]
+    fn test_inside() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let capsule = capsule();
+        for (direction, t) in [(Vec3::new(1, 0, 0), 1.5), (Vec3::new(0, 0, 1), 0.5)] {
+            let hit = capsule
+                .hit(
+                    ray(Point3::new(0, 0, 0), direction),
+                    0.0,
+                    f64::INFINITY,
+                    &mut rng,
+                )
+                .unwrap();
+            assert!((hit.t - t).abs() < 1e-12);
+            assert!(!hit.front_face);
+            assert!((hit.normal + direction).near_zero());
+        }
+    }
+}
@@ -0,0 +1,216 @@
+use rand::prelude::SmallRng;
+
+use crate::{
+    hittable::HitRecord,
+    material::Material,
+    ray::Ray,
+    vec3::{Point3, Vec3},
+};
+
+use super::{
+    frame::{solve_quadratic, turn, Frame, Nearest},
+    Aabb, Hittable,
+};
+
+/// Cone narrowing from a disk of `radius` at `base` to a point at `apex`,
+/// closed by a flat cap at the base.
+///
+/// On the side, `u` goes around the axis and `v` from the base to the apex.
+/// On the cap, `u` goes around the axis and `v` from the center outwards.
+pub struct Cone<M: Material> {
+    frame: Frame,
+    height: f64,
+    radius: f64,
+    material: M,
+}
+
+impl<M: Material> Cone<M> {
+    /// `base` and `apex` must differ
+    pub fn new(base: Point3, apex: Point3, radius: f64, material: M) -> Self {
+        let axis = apex - base;
+        Self {
+            frame: Frame::new(base, axis),
+            height: axis.length(),
+            radius,
+            material,
+        }
+    }
+}
+
+impl<M: Material> Hittable for Cone<M> {
+    fn hit(&self, ray: Ray, t_min: f64, t_max: f64, _rng: &mut SmallRng) -> Option<HitRecord<'_>> {
+        let (o, d) = self.frame.local_ray(ray);
+        let (h, r) = (self.height, self.radius);
+        // Squared slope, the radius is `k (h - z)`
+        let k2 = (r / h) * (r / h);
+        let mut nearest = Nearest::new(t_min, t_max);
+
+        // Side: x² + y² = k² (h - z)²
+        let oh = h - o.z();
+        let a = d.x() * d.x() + d.y() * d.y() - k2 * d.z() * d.z();
+        let half_b = o.x() * d.x() + o.y() * d.y() + k2 * oh * d.z();
+        let c = o.x() * o.x() + o.y() * o.y() - k2 * oh * oh;
+        let roots = if a.abs() > 1e-12 * d.length_squared() {
+            solve_quadratic(a, half_b, c)
+        } else if half_b != 0.0 {
+            // Parallel to the side, with a single crossing
+            let t = -c / (2.0 * half_b);
+            Some((t, t))
+        } else {
+            None
+        };
+        if let Some((t0, t1)) = roots {
+            for t in [t0, t1] {
+                let p = o + t * d;
+                // Excludes the mirrored cone above the apex
+                if (0.0..=h).contains(&p.z()) {
+                    let gradient = Vec3::new(p.x(), p.y(), k2 * (h - p.z()));
+                    let normal = if gradient.near_zero() {
+                        Vec3::new(0, 0, 1)
+                    } else {
+                        gradient.unit_vector()
+                    };
+                    nearest.offer((t, normal, (turn(p.x(), p.y()), p.z() / h)));
+                }
+            }
+        }
+
+        // Base cap, unless the ray is parallel to it
+        if d.z() != 0.0 {
+            let t = -o.z() / d.z();
+            let p = o + t * d;
+            let rho = p.x().hypot(p.y());
+            if rho <= r {
+                let normal = Vec3::new(0, 0, -1);
+                nearest.offer((t, normal, (turn(p.x(), p.y()), rho / r)));
+            }
+        }
+
+        nearest
+            .hit
+            .map(|hit| self.frame.hit_record(ray, hit, &self.material))
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let base = self.frame.disk_bounds(0.0, self.radius);
+        let apex = self.frame.disk_bounds(self.height, 0.0);
+        Some(base.surrounding(&apex))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::{material::Lambertian, vec3::Color};
+
+    fn cone() -> Cone<Lambertian> {
+        Cone::new(
+            Point3::new(0, 0, 0),
+            Point3::new(0, 2, 0),
+            1.0,
+            Lambertian::new(Color::new(0.5, 0.5, 0.5)),
+        )
+    }
+
+    fn ray(origin: Point3, direction: Vec3) -> Ray {
+        Ray {
+            origin,
+            direction,
+            time: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_side_and_cap() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let cone = cone();
+
+        // Halfway up, the radius is 0.5
+        let hit = cone
+            .hit(
+                ray(Point3::new(0, 1, 3), Vec3::new(0, 0, -1)),
+                0.0,
+                f64::INFINITY,
+                &mut rng,
+            )
+            .unwrap();
+        assert!((hit.t - 2.5).abs() < 1e-12);
+        let expected = Vec3::new(0, 1, 2).unit_vector();
+        assert!((hit.normal - expected).near_zero(), "{:?}", hit.normal);
+        assert!((hit.uv.1 - 0.5).abs() < 1e-12);
+
+        let hit = cone
+            .hit(
+                ray(Point3::new(0.5, -2, 0), Vec3::new(0, 1, 0)),
+                0.0,
+                f64::INFINITY,
+                &mut rng,
+            )
+            .unwrap();
+        assert!((hit.t - 2.0).abs() < 1e-12);
+        assert!((hit.normal - Vec3::new(0, -1, 0)).near_zero());
+        assert!((hit.uv.1 - 0.5).abs() < 1e-12);
+
+        // The mirrored cone above the apex is not part of the shape
+        assert!(cone
+            .hit(
+                ray(Point3::new(0, 3, 3), Vec3::new(0, 0, -1)),
+                0.0,
+                f64::INFINITY,
+                &mut rng
+            )
+            .is_none());
+
+        let bbox = cone.bounding_box().unwrap();
+        assert!((bbox.minimum - Point3::new(-1, 0, -1)).near_zero());
+        assert!((bbox.maximum - Point3::new(1, 2, 1)).near_zero());
+    }
+
+    #[test]
+    fn test_grazing() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let cone = cone();
+        // Parallel to one side, crossing the opposite one
+        let hit = cone
+            .hit(
+                ray(Point3::new(0, 4, -1.5), Vec3::new(0, -2, 1)),
+                0.0,
+                f64::INFINITY,
+                &mut rng,
+            )
+            .unwrap();
+        assert!((hit.p - Point3::new(0, 1.5, -0.25)).length() < 1e-9);
+        assert!(hit.front_face);
+
+        // Parallel to the side, just inside and just outside of it
+        let mut graze = |offset: f64| {
+            cone.hit(
+                ray(Point3::new(0, 3, -0.5 + offset), Vec3::new(0, -2, 1)),
+                0.0,
+                f64::INFINITY,
+                &mut rng,
+            )
+        };
+        assert!(graze(-1e-6).is_some());
+        assert!(graze(1e-6).is_none());
+    }
+
+    #[test]
+    fn test_inside() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let cone = cone();
+        let hit = cone
+            .hit(
+                ray(Point3::new(0, 0.5, 0), Vec3::new(0, -1, 0)),
+                0.0,
+                f64::INFINITY,
+                &mut rng,
+            )
+            .unwrap();
+        assert!((hit.t - 0.5).abs() < 1e-12);
+        assert!(!hit.front_face);
+        assert!((hit.normal - Vec3::new(0, 1, 0)).near_zero());
+    }
+}
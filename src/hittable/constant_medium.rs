@@ -0,0 +1,152 @@
+use rand::{prelude::SmallRng, Rng};
+
+use crate::{hittable::HitRecord, material::Material, ray::Ray, vec3::Vec3};
+
+use super::{Aabb, Hittable};
+
+/// Participating medium of uniform density filling a boundary, like smoke or
+/// fog. Rays passing through are scattered at random distances, following
+/// the `phase_function` material.
+///
+/// The boundary must be convex: a ray is assumed to enter and leave it at
+/// most once.
+pub struct ConstantMedium<H: Hittable, M: Material> {
+    boundary: H,
+    neg_inv_density: f64,
+    phase_function: M,
+}
+
+impl<H: Hittable, M: Material> ConstantMedium<H, M> {
+    /// `density` is the chance per unit of distance for a ray to scatter
+    pub fn new(boundary: H, density: f64, phase_function: M) -> Self {
+        Self {
+            boundary,
+            neg_inv_density: -1.0 / density,
+            phase_function,
+        }
+    }
+}
+
+impl<H: Hittable, M: Material> Hittable for ConstantMedium<H, M> {
+    fn hit(&self, ray: Ray, t_min: f64, t_max: f64, rng: &mut SmallRng) -> Option<HitRecord<'_>> {
+        let (t1, t2) = inside(&self.boundary, ray, t_min, t_max, rng)?;
+
+        let ray_length = ray.direction.length();
+        let distance_inside = (t2 - t1) * ray_length;
+        let hit_distance = self.neg_inv_density * rng.gen::<f64>().ln();
+        if hit_distance > distance_inside {
+            return None;
+        }
+
+        let t = t1 + hit_distance / ray_length;
+        Some(volume_hit(ray, t, &self.phase_function))
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.boundary.bounding_box()
+    }
+}
+
+/// Part of `t_min..t_max` where `ray` is inside the convex `boundary`, if any
+pub(super) fn inside(
+    boundary: &dyn Hittable,
+    ray: Ray,
+    t_min: f64,
+    t_max: f64,
+    rng: &mut SmallRng,
+) -> Option<(f64, f64)> {
+    // Where the ray enters and leaves the boundary, even behind its origin
+    let enter = boundary.hit(ray, f64::NEG_INFINITY, f64::INFINITY, rng)?.t;
+    let exit = boundary.hit(ray, enter + 0.0001, f64::INFINITY, rng)?.t;
+
+    let t1 = enter.max(t_min).max(0.0);
+    let t2 = exit.min(t_max);
+    (t1 < t2).then_some((t1, t2))
+}
+
+/// Scattering event at `t` along `ray`, inside a medium
+pub(super) fn volume_hit(ray: Ray, t: f64, material: &dyn Material) -> HitRecord<'_> {
+    // The normal and face are arbitrary inside a volume
+    HitRecord {
+        t,
+        p: ray.at(t),
+        material,
+        normal: Vec3::new(1, 0, 0),
+        geometric_normal: Vec3::new(1, 0, 0),
+        front_face: true,
+        barycentric: (0.0, 0.0),
+        uv: (0.0, 0.0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::{
+        hittable::Sphere,
+        material::{Isotropic, Lambertian},
+        vec3::{Color, Point3},
+    };
+
+    fn fog(density: f64) -> ConstantMedium<Sphere<Lambertian>, Isotropic> {
+        let boundary = Sphere::new(
+            Point3::new(0, 0, 0),
+            1.0,
+            Lambertian::new(Color::new(0.5, 0.5, 0.5)),
+        );
+        ConstantMedium::new(boundary, density, Isotropic::new(Color::new(1, 1, 1)))
+    }
+
+    #[test]
+    fn test_transmittance() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let fog = fog(0.5);
+        let ray = Ray {
+            origin: Point3::new(0, 0, 5),
+            direction: Vec3::new(0, 0, -2),
+            time: 0.0,
+        };
+
+        let n = 20_000;
+        let mut scattered = 0;
+        for _ in 0..n {
+            if let Some(hit) = fog.hit(ray, 0.001, f64::INFINITY, &mut rng) {
+                assert!(hit.p.length() <= 1.0 + 1e-9);
+                scattered += 1;
+            }
+        }
+        // The ray crosses 2 units of the medium
+        let expected = 1.0 - (-2.0 * 0.5f64).exp();
+        let actual = scattered as f64 / n as f64;
+        assert!(
+            (expected - actual).abs() < 0.01,
+            "{} != {}",
+            expected,
+            actual
+        );
+    }
+
+    #[test]
+    fn test_inside() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        // So dense that rays scatter right away
+        let fog = fog(1e9);
+        let ray = Ray {
+            origin: Point3::new(0.5, 0, 0),
+            direction: Vec3::new(1, 0, 0),
+            time: 0.0,
+        };
+        let hit = fog.hit(ray, 0.001, f64::INFINITY, &mut rng).unwrap();
+        assert!((hit.t - 0.001).abs() < 1e-6);
+
+        // Not beyond `t_max`, nor behind the ray
+        assert!(fog.hit(ray, 0.001, 0.0005, &mut rng).is_none());
+        let away = Ray {
+            origin: Point3::new(2, 0, 0),
+            ..ray
+        };
+        assert!(fog.hit(away, 0.001, f64::INFINITY, &mut rng).is_none());
+    }
+}
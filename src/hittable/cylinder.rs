@@ -0,0 +1,198 @@
+use rand::prelude::SmallRng;
+
+use crate::{
+    hittable::HitRecord,
+    material::Material,
+    ray::Ray,
+    vec3::{Point3, Vec3},
+};
+
+use super::{
+    frame::{solve_quadratic, turn, Frame, Nearest},
+    Aabb, Hittable,
+};
+
+/// Cylinder around the segment from `base` to `top`, closed by flat caps.
+///
+/// On the side, `u` goes around the axis and `v` from the base to the top.
+/// On the caps, `u` goes around the axis and `v` from the center outwards.
+pub struct Cylinder<M: Material> {
+    frame: Frame,
+    height: f64,
+    radius: f64,
+    material: M,
+}
+
+impl<M: Material> Cylinder<M> {
+    /// `base` and `top` must differ
+    pub fn new(base: Point3, top: Point3, radius: f64, material: M) -> Self {
+        let axis = top - base;
+        Self {
+            frame: Frame::new(base, axis),
+            height: axis.length(),
+            radius,
+            material,
+        }
+    }
+}
+
+impl<M: Material> Hittable for Cylinder<M> {
+    fn hit(&self, ray: Ray, t_min: f64, t_max: f64, _rng: &mut SmallRng) -> Option<HitRecord<'_>> {
+        let (o, d) = self.frame.local_ray(ray);
+        let (h, r) = (self.height, self.radius);
+        let mut nearest = Nearest::new(t_min, t_max);
+
+        // Side, unless the ray runs along the axis
+        let a = d.x() * d.x() + d.y() * d.y();
+        if a > 0.0 {
+            let half_b = o.x() * d.x() + o.y() * d.y();
+            let c = o.x() * o.x() + o.y() * o.y() - r * r;
+            if let Some((t0, t1)) = solve_quadratic(a, half_b, c) {
+                for t in [t0, t1] {
+                    let p = o + t * d;
+                    if (0.0..=h).contains(&p.z()) {
+                        let normal = Vec3::new(p.x() / r, p.y() / r, 0);
+                        nearest.offer((t, normal, (turn(p.x(), p.y()), p.z() / h)));
+                    }
+                }
+            }
+        }
+
+        // Caps, unless the ray is parallel to them
+        if d.z() != 0.0 {
+            for (z, normal) in [(0.0, -1.0), (h, 1.0)] {
+                let t = (z - o.z()) / d.z();
+                let p = o + t * d;
+                let rho = p.x().hypot(p.y());
+                if rho <= r {
+                    let normal = Vec3::new(0, 0, normal);
+                    nearest.offer((t, normal, (turn(p.x(), p.y()), rho / r)));
+                }
+            }
+        }
+
+        nearest
+            .hit
+            .map(|hit| self.frame.hit_record(ray, hit, &self.material))
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let base = self.frame.disk_bounds(0.0, self.radius);
+        let top = self.frame.disk_bounds(self.height, self.radius);
+        Some(base.surrounding(&top))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::{material::Lambertian, vec3::Color};
+
+    fn cylinder() -> Cylinder<Lambertian> {
+        Cylinder::new(
+            Point3::new(0, -1, 0),
+            Point3::new(0, 1, 0),
+            0.5,
+            Lambertian::new(Color::new(0.5, 0.5, 0.5)),
+        )
+    }
+
+    fn ray(origin: Point3, direction: Vec3) -> Ray {
+        Ray {
+            origin,
+            direction,
+            time: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_side_and_caps() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let cylinder = cylinder();
+
+        let hit = cylinder
+            .hit(
+                ray(Point3::new(0, 0.5, 3), Vec3::new(0, 0, -1)),
+                0.0,
+                f64::INFINITY,
+                &mut rng,
+            )
+            .unwrap();
+        assert!((hit.t - 2.5).abs() < 1e-12);
+        assert!((hit.normal - Vec3::new(0, 0, 1)).near_zero());
+        assert!((hit.uv.1 - 0.75).abs() < 1e-12);
+        assert!(hit.front_face);
+
+        let hit = cylinder
+            .hit(
+                ray(Point3::new(0.2, 5, 0), Vec3::new(0, -2, 0)),
+                0.0,
+                f64::INFINITY,
+                &mut rng,
+            )
+            .unwrap();
+        assert!((hit.t - 2.0).abs() < 1e-12);
+        assert!((hit.normal - Vec3::new(0, 1, 0)).near_zero());
+        assert!((hit.uv.1 - 0.4).abs() < 1e-12);
+
+        // Passes above the top
+        assert!(cylinder
+            .hit(
+                ray(Point3::new(0, 1.5, 3), Vec3::new(0, 0, -1)),
+                0.0,
+                f64::INFINITY,
+                &mut rng
+            )
+            .is_none());
+
+        let bbox = cylinder.bounding_box().unwrap();
+        assert!((bbox.minimum - Point3::new(-0.5, -1, -0.5)).near_zero());
+        assert!((bbox.maximum - Point3::new(0.5, 1, 0.5)).near_zero());
+    }
+
+    #[test]
+    fn test_grazing() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let cylinder = cylinder();
+        let mut graze = |x: f64| {
+            cylinder.hit(
+                ray(Point3::new(x, 0, 3), Vec3::new(0, 0, -1)),
+                0.0,
+                f64::INFINITY,
+                &mut rng,
+            )
+        };
+        assert!(graze(0.5 - 1e-9).is_some());
+        assert!(graze(0.5 + 1e-9).is_none());
+
+        // Along the side, the ray only hits the rim of the cap
+        let hit = cylinder
+            .hit(
+                ray(Point3::new(0.5 - 1e-9, 3, 0), Vec3::new(0, -1, 0)),
+                0.0,
+                f64::INFINITY,
+                &mut rng,
+            )
+            .unwrap();
+        assert!((hit.t - 2.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_inside() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let cylinder = cylinder();
+        let hit = cylinder
+            .hit(
+                ray(Point3::new(0, 0, 0), Vec3::new(1, 0, 0)),
+                0.0,
+                f64::INFINITY,
+                &mut rng,
+            )
+            .unwrap();
+        assert!((hit.t - 0.5).abs() < 1e-12);
+        assert!(!hit.front_face);
+        assert!((hit.normal - Vec3::new(-1, 0, 0)).near_zero());
+    }
+}
@@ -0,0 +1,185 @@
+use std::f64::consts::PI;
+
+use rand::{prelude::SmallRng, Rng};
+
+use crate::{
+    hittable::HitRecord,
+    material::Material,
+    ray::Ray,
+    vec3::{Onb, Point3, Vec3},
+};
+
+use super::{Aabb, Hittable};
+
+/// Rays closer to parallel to the plane than this are ignored
+const EPSILON: f64 = 1e-8;
+
+/// Flat circle facing `normal`
+pub struct Disk<M: Material> {
+    center: Point3,
+    radius: f64,
+    /// `w` is the unit normal
+    basis: Onb,
+    material: M,
+}
+
+impl<M: Material> Disk<M> {
+    pub fn new(center: Point3, normal: Vec3, radius: f64, material: M) -> Self {
+        Self {
+            center,
+            radius,
+            basis: Onb::from_w(normal),
+            material,
+        }
+    }
+}
+
+impl<M: Material> Hittable for Disk<M> {
+    fn hit(&self, ray: Ray, t_min: f64, t_max: f64, _rng: &mut SmallRng) -> Option<HitRecord<'_>> {
+        let normal = self.basis.w;
+        let denom = normal.dot(&ray.direction);
+        if denom.abs() < EPSILON {
+            // parallel to the disk
+            return None;
+        }
+
+        let t = normal.dot(&(self.center - ray.origin)) / denom;
+        if t < t_min || t_max < t {
+            return None;
+        }
+
+        let p = ray.at(t);
+        let offset = p - self.center;
+        let r = offset.length();
+        if r > self.radius {
+            return None;
+        }
+
+        // Polar coordinates, `u` around the center and `v` outwards
+        let phi = offset.dot(&self.basis.v).atan2(offset.dot(&self.basis.u));
+        let u = (phi / (2.0 * PI)).rem_euclid(1.0);
+
+        // We have a hit
+        let mut hit = HitRecord {
+            t,
+            p,
+            material: &self.material,
+            normal: Vec3::default(),
+            geometric_normal: Vec3::default(),
+            front_face: true,
+            barycentric: (0.0, 0.0),
+            uv: (u, r / self.radius),
+        };
+        hit.set_face_normal(ray, normal);
+
+        Some(hit)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        // Extent of the disk along each axis
+        let n = self.basis.w;
+        let e = self.radius
+            * Vec3::new(
+                (1.0 - n.x() * n.x()).max(0.0).sqrt(),
+                (1.0 - n.y() * n.y()).max(0.0).sqrt(),
+                (1.0 - n.z() * n.z()).max(0.0).sqrt(),
+            );
+        // Pad the box so axis-aligned disks don't produce a flat box
+        let padding = Vec3::new(1e-4, 1e-4, 1e-4);
+        Some(Aabb::new(
+            self.center - e - padding,
+            self.center + e + padding,
+        ))
+    }
+
+    /// Density of choosing `direction` from `origin`, when picking points
+    /// evenly over the area
+    fn pdf_value(&self, origin: Point3, direction: Vec3, rng: &mut SmallRng) -> f64 {
+        let ray = Ray {
+            origin,
+            direction,
+            time: 0.0,
+        };
+        let Some(hit) = self.hit(ray, 0.001, f64::INFINITY, rng) else {
+            return 0.0;
+        };
+        let area = PI * self.radius * self.radius;
+        let distance_squared = hit.t * hit.t * direction.length_squared();
+        let cosine = self.basis.w.dot(&direction).abs() / direction.length();
+        distance_squared / (cosine * area)
+    }
+
+    /// Direction from `origin` to a random point of the disk
+    fn sample_direction(&self, origin: Point3, rng: &mut SmallRng) -> Option<Vec3> {
+        let r = self.radius * rng.gen::<f64>().sqrt();
+        let phi = 2.0 * PI * rng.gen::<f64>();
+        let p = self.center + r * phi.cos() * self.basis.u + r * phi.sin() * self.basis.v;
+        Some(p - origin)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::{material::Lambertian, random_unit_vector, vec3::Color};
+
+    #[test]
+    fn test_hit() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let disk = Disk::new(
+            Point3::new(0, 1, 0),
+            Vec3::new(0, 2, 0),
+            2.0,
+            Lambertian::new(Color::new(0.5, 0.5, 0.5)),
+        );
+        let ray = |origin| Ray {
+            origin,
+            direction: Vec3::new(0, -1, 0),
+            time: 0.0,
+        };
+
+        let hit = disk
+            .hit(ray(Point3::new(1, 3, 0)), 0.0, f64::INFINITY, &mut rng)
+            .unwrap();
+        assert_eq!(2.0, hit.t);
+        assert_eq!(Vec3::new(0, 1, 0), hit.normal);
+        assert_eq!(0.5, hit.uv.1);
+
+        assert!(disk
+            .hit(ray(Point3::new(1.5, 3, 1.5)), 0.0, f64::INFINITY, &mut rng)
+            .is_none());
+
+        let bbox = disk.bounding_box().unwrap();
+        assert!((bbox.minimum - Point3::new(-2, 1, -2)).length() < 1e-3);
+        assert!((bbox.maximum - Point3::new(2, 1, 2)).length() < 1e-3);
+    }
+
+    #[test]
+    fn test_sample() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let disk = Disk::new(
+            Point3::new(0, 1, 0),
+            Vec3::new(1, 1, 0),
+            1.5,
+            Lambertian::new(Color::new(0.5, 0.5, 0.5)),
+        );
+        let origin = Point3::new(1, 3, 0.5);
+        for _ in 0..100 {
+            let direction = disk.sample_direction(origin, &mut rng).unwrap();
+            assert!(disk.pdf_value(origin, direction, &mut rng) > 0.0);
+        }
+
+        // The density integrates to one over all directions
+        let n = 200_000;
+        let integral = (0..n)
+            .map(|_| {
+                let direction = random_unit_vector(&mut rng);
+                4.0 * PI * disk.pdf_value(origin, direction, &mut rng)
+            })
+            .sum::<f64>()
+            / n as f64;
+        assert!((integral - 1.0).abs() < 0.02, "{}", integral);
+    }
+}
@@ -0,0 +1,116 @@
+use std::f64::consts::PI;
+
+use crate::{
+    hittable::HitRecord,
+    material::Material,
+    ray::Ray,
+    vec3::{Onb, Point3, Vec3},
+};
+
+use super::Aabb;
+
+/// Distance, outward normal in local coordinates and surface coordinates of
+/// a hit
+pub(super) type LocalHit = (f64, Vec3, (f64, f64));
+
+/// Placement of a shape that is defined in local coordinates around the z
+/// axis, shared by the round shapes
+#[derive(Debug, Clone, Copy)]
+pub(super) struct Frame {
+    origin: Point3,
+    basis: Onb,
+}
+
+impl Frame {
+    /// Frame with its local origin at `origin` and z axis along `axis`
+    pub(super) fn new(origin: Point3, axis: Vec3) -> Self {
+        Self {
+            origin,
+            basis: Onb::from_w(axis),
+        }
+    }
+
+    /// Origin and direction of `ray` in local coordinates. The basis is
+    /// orthonormal, so distances along the ray stay the same.
+    pub(super) fn local_ray(&self, ray: Ray) -> (Point3, Vec3) {
+        (
+            self.basis.coordinates(ray.origin - self.origin),
+            self.basis.coordinates(ray.direction),
+        )
+    }
+
+    pub(super) fn hit_record<'a>(
+        &self,
+        ray: Ray,
+        (t, normal, uv): LocalHit,
+        material: &'a dyn Material,
+    ) -> HitRecord<'a> {
+        let mut hit = HitRecord {
+            t,
+            p: ray.at(t),
+            material,
+            normal: Vec3::default(),
+            geometric_normal: Vec3::default(),
+            front_face: true,
+            barycentric: (0.0, 0.0),
+            uv,
+        };
+        hit.set_face_normal(ray, self.basis.local(normal));
+        hit
+    }
+
+    /// Box enclosing a circle of `radius` around the local z axis at height
+    /// `z`
+    pub(super) fn disk_bounds(&self, z: f64, radius: f64) -> Aabb {
+        let center = self.origin + z * self.basis.w;
+        let w = self.basis.w;
+        let e = radius
+            * Vec3::new(
+                (1.0 - w.x() * w.x()).max(0.0).sqrt(),
+                (1.0 - w.y() * w.y()).max(0.0).sqrt(),
+                (1.0 - w.z() * w.z()).max(0.0).sqrt(),
+            );
+        Aabb::new(center - e, center + e)
+    }
+}
+
+/// Closest of several candidate hits within `t_min..=t_max`
+pub(super) struct Nearest {
+    t_min: f64,
+    t_max: f64,
+    pub(super) hit: Option<LocalHit>,
+}
+
+impl Nearest {
+    pub(super) fn new(t_min: f64, t_max: f64) -> Self {
+        Self {
+            t_min,
+            t_max,
+            hit: None,
+        }
+    }
+
+    /// Keep `hit` if it is in range and closer than the previous ones
+    pub(super) fn offer(&mut self, hit: LocalHit) {
+        if self.t_min <= hit.0 && hit.0 <= self.t_max {
+            self.t_max = hit.0;
+            self.hit = Some(hit);
+        }
+    }
+}
+
+/// Roots of `a t² + 2 half_b t + c`, if there are any. They come in
+/// increasing order for a positive `a`, and `a` must not be zero.
+pub(super) fn solve_quadratic(a: f64, half_b: f64, c: f64) -> Option<(f64, f64)> {
+    let discriminant = half_b * half_b - a * c;
+    if discriminant < 0.0 {
+        return None;
+    }
+    let sqrtd = discriminant.sqrt();
+    Some(((-half_b - sqrtd) / a, (-half_b + sqrtd) / a))
+}
+
+/// Angle of `(x, y)` around the origin as a fraction of a turn, in `[0, 1)`
+pub(super) fn turn(x: f64, y: f64) -> f64 {
+    (y.atan2(x) / (2.0 * PI)).rem_euclid(1.0)
+}
@@ -0,0 +1,201 @@
+use rand::{prelude::SmallRng, Rng};
+
+use crate::{
+    density::Density,
+    hittable::HitRecord,
+    material::{Material, Sample},
+    ray::Ray,
+};
+
+use super::{
+    constant_medium::{inside, volume_hit},
+    Aabb, Hittable,
+};
+
+/// Participating medium whose density varies through its boundary, like
+/// clouds or explosions. Rays are tracked through it against the maximum
+/// density (delta tracking), so the density field is never integrated.
+///
+/// The boundary must be convex: a ray is assumed to enter and leave it at
+/// most once.
+pub struct HeterogeneousMedium<H: Hittable, D: Density, M: Material> {
+    boundary: H,
+    density: D,
+    /// Chance per unit of distance and of density for a ray to be absorbed
+    absorption: f64,
+    /// Chance per unit of distance and of density for a ray to scatter
+    scattering: f64,
+    phase_function: M,
+}
+
+impl<H: Hittable, D: Density, M: Material> HeterogeneousMedium<H, D, M> {
+    /// By default the medium only scatters, once per unit of distance and
+    /// density on average
+    pub fn new(boundary: H, density: D, phase_function: M) -> Self {
+        Self {
+            boundary,
+            density,
+            absorption: 0.0,
+            scattering: 1.0,
+            phase_function,
+        }
+    }
+
+    pub fn with_coefficients(mut self, absorption: f64, scattering: f64) -> Self {
+        self.absorption = absorption;
+        self.scattering = scattering;
+        self
+    }
+
+    /// Extinction at the densest point, per unit of `t` along `ray`
+    fn majorant(&self, ray: Ray) -> f64 {
+        let extinction = self.absorption + self.scattering;
+        self.density.max_density() * extinction * ray.direction.length()
+    }
+}
+
+impl<H: Hittable, D: Density, M: Material> Hittable for HeterogeneousMedium<H, D, M> {
+    fn hit(&self, ray: Ray, t_min: f64, t_max: f64, rng: &mut SmallRng) -> Option<HitRecord<'_>> {
+        let (t1, t2) = inside(&self.boundary, ray, t_min, t_max, rng)?;
+        let majorant = self.majorant(ray);
+        if majorant <= 0.0 {
+            return None;
+        }
+
+        // Step between tentative collisions with the densest possible
+        // medium, and keep each one in proportion to the actual density
+        let max_density = self.density.max_density();
+        let mut t = t1;
+        loop {
+            t -= (1.0 - rng.gen::<f64>()).ln() / majorant;
+            if t >= t2 {
+                return None;
+            }
+            let density = self.density.density(ray.at(t));
+            if rng.gen::<f64>() * max_density < density {
+                break;
+            }
+        }
+
+        let extinction = self.absorption + self.scattering;
+        if rng.gen::<f64>() * extinction < self.absorption {
+            Some(volume_hit(ray, t, &Absorber))
+        } else {
+            Some(volume_hit(ray, t, &self.phase_function))
+        }
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.boundary.bounding_box()
+    }
+}
+
+/// Ends the paths that are absorbed inside a medium
+struct Absorber;
+
+impl Material for Absorber {
+    fn sample(&self, _ray: Ray, _rec: &HitRecord, _rng: &mut SmallRng) -> Sample {
+        Sample::Absorbed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::{
+        density::NoiseDensity,
+        hittable::Sphere,
+        material::{HenyeyGreenstein, Lambertian},
+        vec3::{Color, Point3, Vec3},
+    };
+
+    fn ball<D: Density>(
+        density: D,
+    ) -> HeterogeneousMedium<Sphere<Lambertian>, D, HenyeyGreenstein> {
+        let boundary = Sphere::new(
+            Point3::new(0, 0, 0),
+            1.0,
+            Lambertian::new(Color::new(0.5, 0.5, 0.5)),
+        );
+        HeterogeneousMedium::new(
+            boundary,
+            density,
+            HenyeyGreenstein::new(Color::new(1, 1, 1), 0.5),
+        )
+    }
+
+    /// Density 0.5 in the lower half of space, but claims to go up to 2 so
+    /// that most tentative collisions are rejected
+    struct Half;
+
+    impl Density for Half {
+        fn density(&self, p: Point3) -> f64 {
+            if p.z() < 0.0 {
+                0.5
+            } else {
+                0.0
+            }
+        }
+
+        fn max_density(&self) -> f64 {
+            2.0
+        }
+    }
+
+    fn ray() -> Ray {
+        Ray {
+            origin: Point3::new(0, 0, 5),
+            direction: Vec3::new(0, 0, -2),
+            time: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_constant_density() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let medium = ball(Half).with_coefficients(0.2, 0.8);
+        let ray = ray();
+
+        // The ray crosses 1 unit of density 0.5
+        let expected = (-0.5f64).exp();
+        let n = 20_000;
+        let mut absorbed = 0;
+        let mut scattered = 0;
+        for _ in 0..n {
+            if let Some(hit) = medium.hit(ray, 0.001, f64::INFINITY, &mut rng) {
+                assert!(hit.p.z() <= 1e-9 && hit.p.z() >= -1.0 - 1e-9);
+                match hit.material.sample(ray, &hit, &mut rng) {
+                    Sample::Absorbed => absorbed += 1,
+                    _ => scattered += 1,
+                }
+            }
+        }
+        let actual = 1.0 - (absorbed + scattered) as f64 / n as f64;
+        assert!(
+            (expected - actual).abs() < 0.01,
+            "{} != {}",
+            expected,
+            actual
+        );
+        let ratio = absorbed as f64 / (absorbed + scattered) as f64;
+        assert!((ratio - 0.2).abs() < 0.02, "{}", ratio);
+    }
+
+    #[test]
+    fn test_empty() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let ray = ray();
+        let medium = ball(NoiseDensity::new(0, 0.0));
+        assert!(medium.hit(ray, 0.001, f64::INFINITY, &mut rng).is_none());
+
+        // Nothing outside the boundary either
+        let medium = ball(4.0);
+        let away = Ray {
+            origin: Point3::new(2, 0, 5),
+            ..ray
+        };
+        assert!(medium.hit(away, 0.001, f64::INFINITY, &mut rng).is_none());
+    }
+}
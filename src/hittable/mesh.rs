@@ -0,0 +1,203 @@
+use std::{ops::Range, sync::Arc};
+
+use rand::prelude::SmallRng;
+
+use crate::{
+    hittable::HitRecord,
+    material::Material,
+    ray::Ray,
+    vec3::{Point3, Vec3},
+};
+
+use super::{
+    triangle::{hit_triangle, triangle_bounds},
+    Aabb, BvhNode, Hittable,
+};
+
+/// A triangle of a `Mesh`, given as indices into the mesh's vertex data
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MeshFace {
+    pub positions: [usize; 3],
+    pub normals: Option<[usize; 3]>,
+    pub uvs: Option<[usize; 3]>,
+    /// Index into `MeshData::materials`
+    pub material: usize,
+}
+
+/// A named range of faces, e.g. an OBJ group
+#[derive(Debug, Clone, PartialEq)]
+pub struct MeshGroup {
+    pub name: String,
+    pub faces: Range<usize>,
+}
+
+/// Shared vertex and face data of an indexed triangle mesh
+#[derive(Default)]
+pub struct MeshData {
+    pub positions: Vec<Point3>,
+    pub normals: Vec<Vec3>,
+    pub uvs: Vec<(f64, f64)>,
+    pub faces: Vec<MeshFace>,
+    pub materials: Vec<Box<dyn Material + Send + Sync>>,
+    pub groups: Vec<MeshGroup>,
+}
+
+impl MeshData {
+    fn face_positions(&self, face: &MeshFace) -> [Point3; 3] {
+        face.positions.map(|i| self.positions[i])
+    }
+}
+
+/// Indexed triangle mesh. Faces are kept in their own BVH.
+pub struct Mesh {
+    data: Arc<MeshData>,
+    bvh: BvhNode,
+}
+
+impl Mesh {
+    /// Build a mesh from its data.
+    ///
+    /// Panics if any face refers to a vertex attribute or material that does
+    /// not exist.
+    pub fn new(data: MeshData) -> Self {
+        for face in &data.faces {
+            assert!(face.positions.iter().all(|&i| i < data.positions.len()));
+            if let Some(normals) = face.normals {
+                assert!(normals.iter().all(|&i| i < data.normals.len()));
+            }
+            if let Some(uvs) = face.uvs {
+                assert!(uvs.iter().all(|&i| i < data.uvs.len()));
+            }
+            assert!(face.material < data.materials.len());
+        }
+
+        let data = Arc::new(data);
+        let bvh = BvhNode::new(
+            (0..data.faces.len())
+                .map(|face| {
+                    Box::new(MeshTriangle {
+                        mesh: Arc::clone(&data),
+                        face,
+                    }) as Box<dyn Hittable + Send + Sync>
+                })
+                .collect(),
+        );
+
+        Self { data, bvh }
+    }
+
+    pub fn data(&self) -> &MeshData {
+        &self.data
+    }
+}
+
+impl Hittable for Mesh {
+    fn hit(&self, ray: Ray, t_min: f64, t_max: f64, rng: &mut SmallRng) -> Option<HitRecord<'_>> {
+        self.bvh.hit(ray, t_min, t_max, rng)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.bvh.bounding_box()
+    }
+}
+
+struct MeshTriangle {
+    mesh: Arc<MeshData>,
+    face: usize,
+}
+
+impl Hittable for MeshTriangle {
+    fn hit(&self, ray: Ray, t_min: f64, t_max: f64, _rng: &mut SmallRng) -> Option<HitRecord<'_>> {
+        let face = &self.mesh.faces[self.face];
+        hit_triangle(
+            self.mesh.face_positions(face),
+            face.normals
+                .map(|normals| normals.map(|i| self.mesh.normals[i])),
+            face.uvs.map(|uvs| uvs.map(|i| self.mesh.uvs[i])),
+            self.mesh.materials[face.material].as_ref(),
+            ray,
+            t_min,
+            t_max,
+        )
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let face = &self.mesh.faces[self.face];
+        Some(triangle_bounds(self.mesh.face_positions(face)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::{material::Lambertian, vec3::Color};
+
+    /// Unit square in the XY plane, made of two triangles
+    fn square() -> Mesh {
+        Mesh::new(MeshData {
+            positions: vec![
+                Point3::new(0, 0, 0),
+                Point3::new(1, 0, 0),
+                Point3::new(1, 1, 0),
+                Point3::new(0, 1, 0),
+            ],
+            faces: vec![
+                MeshFace {
+                    positions: [0, 1, 2],
+                    normals: None,
+                    uvs: None,
+                    material: 0,
+                },
+                MeshFace {
+                    positions: [0, 2, 3],
+                    normals: None,
+                    uvs: None,
+                    material: 0,
+                },
+            ],
+            materials: vec![Box::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)))],
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_hit() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let mesh = square();
+        for (x, y) in [(0.75, 0.25), (0.25, 0.75)] {
+            let ray = Ray {
+                origin: Point3::new(x, y, 1),
+                direction: Vec3::new(0, 0, -1),
+                time: 0.0,
+            };
+            let hit = mesh.hit(ray, 0.0, f64::INFINITY, &mut rng).unwrap();
+            assert_eq!(1.0, hit.t);
+            assert_eq!(Vec3::new(0, 0, 1), hit.normal);
+        }
+
+        let ray = Ray {
+            origin: Point3::new(1.5, 0.5, 1),
+            direction: Vec3::new(0, 0, -1),
+            time: 0.0,
+        };
+        assert!(mesh.hit(ray, 0.0, f64::INFINITY, &mut rng).is_none());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_invalid_index() {
+        Mesh::new(MeshData {
+            positions: vec![Point3::new(0, 0, 0)],
+            faces: vec![MeshFace {
+                positions: [0, 1, 2],
+                normals: None,
+                uvs: None,
+                material: 0,
+            }],
+            materials: vec![Box::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)))],
+            ..Default::default()
+        });
+    }
+}
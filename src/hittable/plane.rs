@@ -0,0 +1,107 @@
+use rand::prelude::SmallRng;
+
+use crate::{
+    hittable::HitRecord,
+    material::Material,
+    ray::Ray,
+    vec3::{Onb, Point3, Vec3},
+};
+
+use super::{Aabb, Hittable};
+
+/// Rays closer to parallel to the plane than this are ignored
+const EPSILON: f64 = 1e-8;
+
+/// Infinite plane through `point`, facing `normal`. UVs are the distances
+/// from `point` along two directions in the plane, so textures repeat.
+pub struct Plane<M: Material> {
+    point: Point3,
+    /// `w` is the unit normal
+    basis: Onb,
+    material: M,
+}
+
+impl<M: Material> Plane<M> {
+    pub fn new(point: Point3, normal: Vec3, material: M) -> Self {
+        Self {
+            point,
+            basis: Onb::from_w(normal),
+            material,
+        }
+    }
+}
+
+impl<M: Material> Hittable for Plane<M> {
+    fn hit(&self, ray: Ray, t_min: f64, t_max: f64, _rng: &mut SmallRng) -> Option<HitRecord<'_>> {
+        let normal = self.basis.w;
+        let denom = normal.dot(&ray.direction);
+        if denom.abs() < EPSILON {
+            // parallel to the plane
+            return None;
+        }
+
+        let t = normal.dot(&(self.point - ray.origin)) / denom;
+        if t < t_min || t_max < t {
+            return None;
+        }
+
+        let p = ray.at(t);
+        let offset = p - self.point;
+
+        // We have a hit
+        let mut hit = HitRecord {
+            t,
+            p,
+            material: &self.material,
+            normal: Vec3::default(),
+            geometric_normal: Vec3::default(),
+            front_face: true,
+            barycentric: (0.0, 0.0),
+            uv: (offset.dot(&self.basis.u), offset.dot(&self.basis.v)),
+        };
+        hit.set_face_normal(ray, normal);
+
+        Some(hit)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::{material::Lambertian, vec3::Color};
+
+    #[test]
+    fn test_hit() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let plane = Plane::new(
+            Point3::new(0, -1, 0),
+            Vec3::new(0, 1, 0),
+            Lambertian::new(Color::new(0.5, 0.5, 0.5)),
+        );
+        let ray = Ray {
+            origin: Point3::new(3, 1, -4),
+            direction: Vec3::new(1, -1, 0),
+            time: 0.0,
+        };
+
+        let hit = plane.hit(ray, 0.0, f64::INFINITY, &mut rng).unwrap();
+        assert_eq!(2.0, hit.t);
+        assert_eq!(Point3::new(5, -1, -4), hit.p);
+        assert_eq!(Vec3::new(0, 1, 0), hit.normal);
+        let (u, v) = hit.uv;
+        assert!((u * u + v * v - 41.0).abs() < 1e-9);
+
+        let up = Ray {
+            direction: Vec3::new(0, 1, 0),
+            ..ray
+        };
+        assert!(plane.hit(up, 0.0, f64::INFINITY, &mut rng).is_none());
+        assert!(plane.bounding_box().is_none());
+    }
+}
@@ -0,0 +1,362 @@
+use rand::{prelude::SmallRng, Rng};
+
+use crate::{
+    hittable::HitRecord,
+    material::Material,
+    ray::Ray,
+    vec3::{Point3, Vec3},
+};
+
+use super::{Aabb, Hittable};
+
+/// Rays closer to parallel to the plane than this are ignored
+const EPSILON: f64 = 1e-8;
+
+/// Geometry of a parallelogram, shared by `Quad` and the sides of a `Cuboid`
+#[derive(Debug, Clone, Copy)]
+pub(super) struct Parallelogram {
+    q: Point3,
+    u: Vec3,
+    v: Vec3,
+    /// Unit normal, `u × v`
+    normal: Vec3,
+    /// Plane offset, `normal · q`
+    d: f64,
+    /// Maps points in the plane to coordinates along `u` and `v`
+    w: Vec3,
+}
+
+impl Parallelogram {
+    pub(super) fn new(q: Point3, u: Vec3, v: Vec3) -> Self {
+        let n = u.cross(v);
+        let normal = n.unit_vector();
+        Self {
+            q,
+            u,
+            v,
+            normal,
+            d: normal.dot(&q),
+            w: n / n.length_squared(),
+        }
+    }
+
+    /// Distance and coordinates along `u` and `v` where `ray` crosses the
+    /// parallelogram
+    fn intersect(&self, ray: Ray, t_min: f64, t_max: f64) -> Option<(f64, f64, f64)> {
+        let denom = self.normal.dot(&ray.direction);
+        if denom.abs() < EPSILON {
+            // parallel to the plane
+            return None;
+        }
+
+        let t = (self.d - self.normal.dot(&ray.origin)) / denom;
+        if t < t_min || t_max < t {
+            return None;
+        }
+
+        let planar = ray.at(t) - self.q;
+        let alpha = self.w.dot(&planar.cross(self.v));
+        let beta = self.w.dot(&self.u.cross(planar));
+        if !(0.0..=1.0).contains(&alpha) || !(0.0..=1.0).contains(&beta) {
+            return None;
+        }
+        Some((t, alpha, beta))
+    }
+
+    pub(super) fn hit<'a>(
+        &self,
+        material: &'a dyn Material,
+        ray: Ray,
+        t_min: f64,
+        t_max: f64,
+    ) -> Option<HitRecord<'a>> {
+        let (t, alpha, beta) = self.intersect(ray, t_min, t_max)?;
+
+        // We have a hit
+        let mut hit = HitRecord {
+            t,
+            p: ray.at(t),
+            material,
+            normal: Vec3::default(),
+            geometric_normal: Vec3::default(),
+            front_face: true,
+            barycentric: (0.0, 0.0),
+            uv: (alpha, beta),
+        };
+        hit.set_face_normal(ray, self.normal);
+
+        Some(hit)
+    }
+
+    /// Density of choosing `direction` from `origin`, when picking points
+    /// evenly over the area
+    pub(super) fn pdf_value(&self, origin: Point3, direction: Vec3) -> f64 {
+        let ray = Ray {
+            origin,
+            direction,
+            time: 0.0,
+        };
+        let Some((t, _, _)) = self.intersect(ray, 0.001, f64::INFINITY) else {
+            return 0.0;
+        };
+        let area = self.u.cross(self.v).length();
+        let distance_squared = t * t * direction.length_squared();
+        let cosine = self.normal.dot(&direction).abs() / direction.length();
+        distance_squared / (cosine * area)
+    }
+
+    /// Direction from `origin` to a random point of the parallelogram
+    pub(super) fn sample_direction(&self, origin: Point3, rng: &mut SmallRng) -> Vec3 {
+        let p = self.q + rng.gen::<f64>() * self.u + rng.gen::<f64>() * self.v;
+        p - origin
+    }
+
+    pub(super) fn bounds(&self) -> Aabb {
+        let corners = [
+            self.q,
+            self.q + self.u,
+            self.q + self.v,
+            self.q + self.u + self.v,
+        ];
+        let bbox = corners
+            .iter()
+            .fold(Aabb::empty(), |acc, &p| acc.surrounding(&Aabb::new(p, p)));
+        // Pad the box so axis-aligned quads don't produce a flat box
+        let padding = Vec3::new(1e-4, 1e-4, 1e-4);
+        Aabb::new(bbox.minimum - padding, bbox.maximum + padding)
+    }
+}
+
+/// Parallelogram with corners `q`, `q + u`, `q + v` and `q + u + v`. The
+/// front face is the one `u × v` points out of.
+pub struct Quad<M: Material> {
+    shape: Parallelogram,
+    material: M,
+}
+
+impl<M: Material> Quad<M> {
+    pub fn new(q: Point3, u: Vec3, v: Vec3, material: M) -> Self {
+        Self {
+            shape: Parallelogram::new(q, u, v),
+            material,
+        }
+    }
+}
+
+impl<M: Material> Hittable for Quad<M> {
+    fn hit(&self, ray: Ray, t_min: f64, t_max: f64, _rng: &mut SmallRng) -> Option<HitRecord<'_>> {
+        self.shape.hit(&self.material, ray, t_min, t_max)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(self.shape.bounds())
+    }
+
+    fn pdf_value(&self, origin: Point3, direction: Vec3, _rng: &mut SmallRng) -> f64 {
+        self.shape.pdf_value(origin, direction)
+    }
+
+    fn sample_direction(&self, origin: Point3, rng: &mut SmallRng) -> Option<Vec3> {
+        Some(self.shape.sample_direction(origin, rng))
+    }
+}
+
+/// Axis-aligned box made of six quads facing outwards
+pub struct Cuboid<M: Material> {
+    sides: [Parallelogram; 6],
+    bbox: Aabb,
+    material: M,
+}
+
+impl<M: Material> Cuboid<M> {
+    /// Box between two opposite corners
+    pub fn new(a: Point3, b: Point3, material: M) -> Self {
+        let min = Point3::new(a.x().min(b.x()), a.y().min(b.y()), a.z().min(b.z()));
+        let max = Point3::new(a.x().max(b.x()), a.y().max(b.y()), a.z().max(b.z()));
+
+        let dx = Vec3::new(max.x() - min.x(), 0, 0);
+        let dy = Vec3::new(0, max.y() - min.y(), 0);
+        let dz = Vec3::new(0, 0, max.z() - min.z());
+
+        let sides = [
+            // front
+            Parallelogram::new(Point3::new(min.x(), min.y(), max.z()), dx, dy),
+            // right
+            Parallelogram::new(Point3::new(max.x(), min.y(), max.z()), -dz, dy),
+            // back
+            Parallelogram::new(Point3::new(max.x(), min.y(), min.z()), -dx, dy),
+            // left
+            Parallelogram::new(min, dz, dy),
+            // top
+            Parallelogram::new(Point3::new(min.x(), max.y(), max.z()), dx, -dz),
+            // bottom
+            Parallelogram::new(min, dx, dz),
+        ];
+        let bbox = sides
+            .iter()
+            .fold(Aabb::empty(), |acc, side| acc.surrounding(&side.bounds()));
+
+        Self {
+            sides,
+            bbox,
+            material,
+        }
+    }
+}
+
+impl<M: Material> Hittable for Cuboid<M> {
+    fn hit(&self, ray: Ray, t_min: f64, t_max: f64, _rng: &mut SmallRng) -> Option<HitRecord<'_>> {
+        let mut hit = None;
+        let mut closest_so_far = t_max;
+
+        for side in &self.sides {
+            if let Some(rec) = side.hit(&self.material, ray, t_min, closest_so_far) {
+                closest_so_far = rec.t;
+                hit = Some(rec);
+            }
+        }
+
+        hit
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(self.bbox)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::{material::Lambertian, random_unit_vector, vec3::Color};
+
+    fn material() -> Lambertian {
+        Lambertian::new(Color::new(0.5, 0.5, 0.5))
+    }
+
+    fn ray(origin: Point3, direction: Vec3) -> Ray {
+        Ray {
+            origin,
+            direction,
+            time: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_quad() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let quad = Quad::new(
+            Point3::new(0, 0, 0),
+            Vec3::new(2, 0, 0),
+            Vec3::new(0, 4, 0),
+            material(),
+        );
+
+        let hit = quad
+            .hit(
+                ray(Point3::new(0.5, 1, 3), Vec3::new(0, 0, -1)),
+                0.0,
+                f64::INFINITY,
+                &mut rng,
+            )
+            .unwrap();
+        assert_eq!(3.0, hit.t);
+        assert_eq!(Point3::new(0.5, 1, 0), hit.p);
+        assert_eq!(Vec3::new(0, 0, 1), hit.normal);
+        assert_eq!((0.25, 0.25), hit.uv);
+        assert!(hit.front_face);
+
+        let hit = quad
+            .hit(
+                ray(Point3::new(1, 3, -1), Vec3::new(0, 0, 1)),
+                0.0,
+                f64::INFINITY,
+                &mut rng,
+            )
+            .unwrap();
+        assert_eq!(Vec3::new(0, 0, -1), hit.normal);
+        assert!(!hit.front_face);
+
+        // Outside, and parallel
+        assert!(quad
+            .hit(
+                ray(Point3::new(2.5, 1, 3), Vec3::new(0, 0, -1)),
+                0.0,
+                f64::INFINITY,
+                &mut rng
+            )
+            .is_none());
+        assert!(quad
+            .hit(
+                ray(Point3::new(0.5, 1, 3), Vec3::new(1, 0, 0)),
+                0.0,
+                f64::INFINITY,
+                &mut rng
+            )
+            .is_none());
+    }
+
+    #[test]
+    fn test_sample() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let quad = Quad::new(
+            Point3::new(-1, -2, 0),
+            Vec3::new(2, 0, 0),
+            Vec3::new(0, 4, 0),
+            material(),
+        );
+        let origin = Point3::new(0.5, 0, 2);
+        for _ in 0..100 {
+            let direction = quad.sample_direction(origin, &mut rng).unwrap();
+            assert!(quad.pdf_value(origin, direction, &mut rng) > 0.0);
+        }
+
+        // The density integrates to one over all directions
+        let n = 200_000;
+        let integral = (0..n)
+            .map(|_| {
+                let direction = random_unit_vector(&mut rng);
+                4.0 * PI * quad.pdf_value(origin, direction, &mut rng)
+            })
+            .sum::<f64>()
+            / n as f64;
+        assert!((integral - 1.0).abs() < 0.02, "{}", integral);
+    }
+
+    #[test]
+    fn test_cuboid() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let cuboid = Cuboid::new(Point3::new(1, 1, 1), Point3::new(-1, -1, -1), material());
+
+        for (origin, normal) in [
+            (Point3::new(0, 0, 5), Vec3::new(0, 0, 1)),
+            (Point3::new(0, 0, -5), Vec3::new(0, 0, -1)),
+            (Point3::new(5, 0, 0), Vec3::new(1, 0, 0)),
+            (Point3::new(-5, 0, 0), Vec3::new(-1, 0, 0)),
+            (Point3::new(0, 5, 0), Vec3::new(0, 1, 0)),
+            (Point3::new(0, -5, 0), Vec3::new(0, -1, 0)),
+        ] {
+            let hit = cuboid
+                .hit(ray(origin, -origin), 0.0, f64::INFINITY, &mut rng)
+                .unwrap();
+            assert_eq!(0.8, hit.t);
+            assert_eq!(normal, hit.normal);
+            assert!(hit.front_face, "{:?}", normal);
+        }
+
+        // From inside, the closest side is the one behind the ray
+        let hit = cuboid
+            .hit(
+                ray(Point3::new(0, 0, 0), Vec3::new(0, 0, 1)),
+                0.0,
+                f64::INFINITY,
+                &mut rng,
+            )
+            .unwrap();
+        assert_eq!(1.0, hit.t);
+        assert!(!hit.front_face);
+    }
+}
@@ -1,11 +1,15 @@
+use std::f64::consts::PI;
+
+use rand::{prelude::SmallRng, Rng};
+
 use crate::{
     hittable::HitRecord,
     material::Material,
     ray::Ray,
-    vec3::{Point3, Vec3},
+    vec3::{Onb, Point3, Vec3},
 };
 
-use super::Hittable;
+use super::{Aabb, Hittable};
 
 pub struct Sphere<M: Material> {
     pub center: Point3,
@@ -21,43 +25,247 @@ impl<M: Material> Sphere<M> {
             material,
         }
     }
+
+    /// Cosine of the angle between the center and the edge of the sphere as
+    /// seen from `origin`, or `None` from inside of it
+    fn cos_theta_max(&self, origin: Point3) -> Option<f64> {
+        let distance_squared = (self.center - origin).length_squared();
+        let radius_squared = self.radius * self.radius;
+        if distance_squared <= radius_squared {
+            return None;
+        }
+        Some((1.0 - radius_squared / distance_squared).sqrt())
+    }
 }
 
-impl<M: 'static + Material + Clone> Hittable for Sphere<M> {
-    fn hit(&self, ray: Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
-        let oc = ray.origin - self.center;
-        let a = ray.direction.length_squared();
-        let half_b = oc.dot(&ray.direction);
-        let c = oc.length_squared() - self.radius * self.radius;
+impl<M: Material> Hittable for Sphere<M> {
+    fn hit(&self, ray: Ray, t_min: f64, t_max: f64, _rng: &mut SmallRng) -> Option<HitRecord<'_>> {
+        hit_sphere(self.center, self.radius, &self.material, ray, t_min, t_max)
+    }
 
-        let discriminant = half_b * half_b - a * c;
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(sphere_bounds(self.center, self.radius))
+    }
 
-        if discriminant < 0.0 {
-            // did not hit
-            return None;
+    /// Uniform over the cone of directions in which the sphere is seen
+    fn pdf_value(&self, origin: Point3, direction: Vec3, rng: &mut SmallRng) -> f64 {
+        let ray = Ray {
+            origin,
+            direction,
+            time: 0.0,
+        };
+        if self.hit(ray, 0.001, f64::INFINITY, rng).is_none() {
+            return 0.0;
+        }
+        match self.cos_theta_max(origin) {
+            Some(cos_theta_max) => 1.0 / (2.0 * PI * (1.0 - cos_theta_max)),
+            None => 0.0,
+        }
+    }
+
+    fn sample_direction(&self, origin: Point3, rng: &mut SmallRng) -> Option<Vec3> {
+        let cos_theta_max = self.cos_theta_max(origin)?;
+        let cos_theta = 1.0 + rng.gen::<f64>() * (cos_theta_max - 1.0);
+        let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+        let phi = 2.0 * PI * rng.gen::<f64>();
+        let direction = Onb::from_w(self.center - origin).local(Vec3::new(
+            sin_theta * phi.cos(),
+            sin_theta * phi.sin(),
+            cos_theta,
+        ));
+        Some(direction)
+    }
+}
+
+/// A sphere moving in a straight line from `center0` at `time0` to `center1`
+/// at `time1`, for motion blur
+pub struct MovingSphere<M: Material> {
+    pub center0: Point3,
+    pub center1: Point3,
+    pub time0: f64,
+    pub time1: f64,
+    pub radius: f64,
+    pub material: M,
+}
+
+impl<M: Material> MovingSphere<M> {
+    pub fn new(
+        center0: Point3,
+        center1: Point3,
+        time0: f64,
+        time1: f64,
+        radius: f64,
+        material: M,
+    ) -> Self {
+        Self {
+            center0,
+            center1,
+            time0,
+            time1,
+            radius,
+            material,
         }
+    }
 
-        // Find nearset root that lies in acceptable range
-        let sqrtd = discriminant.sqrt();
-        let mut root = (-half_b - sqrtd) / a;
+    /// Center at `time`. The sphere rests at `center0` before `time0` and at
+    /// `center1` after `time1`, so it never leaves its bounding box.
+    pub fn center(&self, time: f64) -> Point3 {
+        if self.time1 == self.time0 {
+            return self.center0;
+        }
+        let f = ((time - self.time0) / (self.time1 - self.time0)).clamp(0.0, 1.0);
+        self.center0 + f * (self.center1 - self.center0)
+    }
+}
+
+impl<M: Material> Hittable for MovingSphere<M> {
+    fn hit(&self, ray: Ray, t_min: f64, t_max: f64, _rng: &mut SmallRng) -> Option<HitRecord<'_>> {
+        let center = self.center(ray.time);
+        hit_sphere(center, self.radius, &self.material, ray, t_min, t_max)
+    }
+
+    /// Encloses the sphere between `time0` and `time1`
+    fn bounding_box(&self) -> Option<Aabb> {
+        let box0 = sphere_bounds(self.center0, self.radius);
+        let box1 = sphere_bounds(self.center1, self.radius);
+        Some(box0.surrounding(&box1))
+    }
+}
+
+fn hit_sphere(
+    center: Point3,
+    radius: f64,
+    material: &dyn Material,
+    ray: Ray,
+    t_min: f64,
+    t_max: f64,
+) -> Option<HitRecord<'_>> {
+    let oc = ray.origin - center;
+    let a = ray.direction.length_squared();
+    let half_b = oc.dot(&ray.direction);
+    let c = oc.length_squared() - radius * radius;
+
+    let discriminant = half_b * half_b - a * c;
+
+    if discriminant < 0.0 {
+        // did not hit
+        return None;
+    }
+
+    // Find nearset root that lies in acceptable range
+    let sqrtd = discriminant.sqrt();
+    let mut root = (-half_b - sqrtd) / a;
+    if root < t_min || t_max < root {
+        root = (-half_b + sqrtd) / a;
         if root < t_min || t_max < root {
-            root = (-half_b + sqrtd) / a;
-            if root < t_min || t_max < root {
-                return None;
-            }
-        }
-
-        // We have a hit
-        let mut hit = HitRecord {
-            t: root,
-            p: ray.at(root),
-            material: Box::new(self.material.clone()),
-            normal: Vec3::default(),
-            front_face: true,
+            return None;
+        }
+    }
+
+    // We have a hit
+    let mut hit = HitRecord {
+        t: root,
+        p: ray.at(root),
+        material,
+        normal: Vec3::default(),
+        geometric_normal: Vec3::default(),
+        front_face: true,
+        barycentric: (0.0, 0.0),
+        uv: (0.0, 0.0),
+    };
+    let outward_normal = (hit.p - center) / radius;
+    hit.set_face_normal(ray, outward_normal);
+    hit.uv = sphere_uv(outward_normal);
+
+    Some(hit)
+}
+
+fn sphere_bounds(center: Point3, radius: f64) -> Aabb {
+    let r = Vec3::new(radius, radius, radius);
+    Aabb::new(center - r, center + r)
+}
+
+/// Surface coordinates of a point on the unit sphere: `u` goes around the
+/// y axis starting at -x, `v` from the bottom to the top.
+fn sphere_uv(p: Point3) -> (f64, f64) {
+    let theta = (-p.y()).clamp(-1.0, 1.0).acos();
+    let phi = (-p.z()).atan2(p.x()) + PI;
+    (phi / (2.0 * PI), theta / PI)
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::{material::Lambertian, random_unit_vector, vec3::Color};
+
+    #[test]
+    fn test_sphere_uv() {
+        assert_eq!((0.0, 0.5), sphere_uv(Point3::new(-1, 0, 0)));
+        assert_eq!((0.5, 0.5), sphere_uv(Point3::new(1, 0, 0)));
+        assert_eq!((0.25, 0.5), sphere_uv(Point3::new(0, 0, 1)));
+        assert_eq!((0.75, 0.5), sphere_uv(Point3::new(0, 0, -1)));
+        assert_eq!(0.0, sphere_uv(Point3::new(0, -1, 0)).1);
+        assert_eq!(1.0, sphere_uv(Point3::new(0, 1, 0)).1);
+    }
+
+    #[test]
+    fn test_sample() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let sphere = Sphere::new(
+            Point3::new(0, 1, 0),
+            1.0,
+            Lambertian::new(Color::new(0.5, 0.5, 0.5)),
+        );
+        let origin = Point3::new(0, -1, 0.5);
+        for _ in 0..100 {
+            let direction = sphere.sample_direction(origin, &mut rng).unwrap();
+            assert!(sphere.pdf_value(origin, direction, &mut rng) > 0.0);
+        }
+
+        // The density integrates to one over all directions
+        let n = 200_000;
+        let integral = (0..n)
+            .map(|_| {
+                let direction = random_unit_vector(&mut rng);
+                4.0 * PI * sphere.pdf_value(origin, direction, &mut rng)
+            })
+            .sum::<f64>()
+            / n as f64;
+        assert!((integral - 1.0).abs() < 0.02, "{}", integral);
+
+        // Not from the inside
+        let inside = Point3::new(0, 1.5, 0);
+        assert!(sphere.sample_direction(inside, &mut rng).is_none());
+    }
+
+    #[test]
+    fn test_moving_sphere() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let sphere = MovingSphere::new(
+            Point3::new(0, 0, 0),
+            Point3::new(2, 0, 0),
+            0.0,
+            1.0,
+            0.5,
+            Lambertian::new(Color::new(0.5, 0.5, 0.5)),
+        );
+        assert_eq!(Point3::new(1, 0, 0), sphere.center(0.5));
+        assert_eq!(Point3::new(0, 0, 0), sphere.center(-1.0));
+        assert_eq!(Point3::new(2, 0, 0), sphere.center(3.0));
+
+        let ray = |time| Ray {
+            origin: Point3::new(2, 0, 5),
+            direction: Vec3::new(0, 0, -1),
+            time,
         };
-        let outward_normal = (hit.p - self.center) / self.radius;
-        hit.set_face_normal(ray, outward_normal);
+        assert!(sphere.hit(ray(0.0), 0.0, f64::INFINITY, &mut rng).is_none());
+        let hit = sphere.hit(ray(1.0), 0.0, f64::INFINITY, &mut rng).unwrap();
+        assert_eq!(4.5, hit.t);
 
-        Some(hit)
+        let bbox = sphere.bounding_box().unwrap();
+        assert_eq!(Point3::new(-0.5, -0.5, -0.5), bbox.minimum);
+        assert_eq!(Point3::new(2.5, 0.5, 0.5), bbox.maximum);
     }
 }
@@ -0,0 +1,377 @@
+use std::f64::consts::PI;
+
+use rand::prelude::SmallRng;
+
+use crate::{
+    hittable::HitRecord,
+    material::Material,
+    ray::Ray,
+    vec3::{Point3, Vec3},
+};
+
+use super::{
+    frame::{solve_quadratic, turn, Frame, Nearest},
+    Aabb, Hittable,
+};
+
+/// Ring around `axis` through `center`: the points within `minor_radius` of
+/// the circle of `major_radius` around the axis.
+///
+/// `u` goes around the axis and `v` around the tube, starting on the outside.
+pub struct Torus<M: Material> {
+    frame: Frame,
+    major_radius: f64,
+    minor_radius: f64,
+    material: M,
+}
+
+impl<M: Material> Torus<M> {
+    pub fn new(
+        center: Point3,
+        axis: Vec3,
+        major_radius: f64,
+        minor_radius: f64,
+        material: M,
+    ) -> Self {
+        Self {
+            frame: Frame::new(center, axis),
+            major_radius,
+            minor_radius,
+            material,
+        }
+    }
+}
+
+impl<M: Material> Hittable for Torus<M> {
+    fn hit(&self, ray: Ray, t_min: f64, t_max: f64, _rng: &mut SmallRng) -> Option<HitRecord<'_>> {
+        let (o, d) = self.frame.local_ray(ray);
+        let (big, small) = (self.major_radius, self.minor_radius);
+
+        // Solve for the distance `s` along the normalized direction
+        let length = d.length();
+        let d = d / length;
+
+        // Start from where the ray enters the bounding sphere, which keeps the
+        // coefficients of the quartic small for distant rays
+        let outer = big + small;
+        let (s0, _) = solve_quadratic(1.0, o.dot(&d), o.length_squared() - outer * outer)?;
+        let o = o + s0 * d;
+
+        // (|p|² + R² - r²)² = 4 R² (x² + y²) along p = o + s d
+        let n = o.dot(&d);
+        let k = o.length_squared() + big * big - small * small;
+        let big4 = 4.0 * big * big;
+        let coefficients = [
+            4.0 * n,
+            4.0 * n * n + 2.0 * k - big4 * (d.x() * d.x() + d.y() * d.y()),
+            4.0 * n * k - 2.0 * big4 * (o.x() * d.x() + o.y() * d.y()),
+            k * k - big4 * (o.x() * o.x() + o.y() * o.y()),
+        ];
+
+        let mut nearest = Nearest::new(t_min, t_max);
+        for &s in solve_quartic(coefficients).as_slice() {
+            let p = o + s * d;
+            let rho = p.x().hypot(p.y());
+            // Closest point on the circle at the core of the tube
+            let core = if rho > 0.0 {
+                Point3::new(big * p.x() / rho, big * p.y() / rho, 0)
+            } else {
+                Point3::new(big, 0, 0)
+            };
+            let normal = (p - core).unit_vector();
+            let uv = (turn(p.x(), p.y()), turn(rho - big, p.z()));
+            nearest.offer(((s0 + s) / length, normal, uv));
+        }
+
+        nearest
+            .hit
+            .map(|hit| self.frame.hit_record(ray, hit, &self.material))
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let (big, small) = (self.major_radius, self.minor_radius);
+        let bottom = self.frame.disk_bounds(-small, big + small);
+        let top = self.frame.disk_bounds(small, big + small);
+        Some(bottom.surrounding(&top))
+    }
+}
+
+/// Coefficients closer to zero than this are treated as zero
+const EPSILON: f64 = 1e-9;
+
+/// Up to four real roots of a polynomial
+#[derive(Debug, Default)]
+struct Roots {
+    values: [f64; 4],
+    len: usize,
+}
+
+impl Roots {
+    fn push(&mut self, x: f64) {
+        self.values[self.len] = x;
+        self.len += 1;
+    }
+
+    fn as_slice(&self) -> &[f64] {
+        &self.values[..self.len]
+    }
+}
+
+/// Real roots of `x² + p x + q`
+fn solve_normalized_quadratic(p: f64, q: f64, roots: &mut Roots) {
+    let half_p = p / 2.0;
+    let discriminant = half_p * half_p - q;
+    if discriminant.abs() < EPSILON {
+        roots.push(-half_p);
+    } else if discriminant > 0.0 {
+        let sqrtd = discriminant.sqrt();
+        roots.push(-half_p - sqrtd);
+        roots.push(-half_p + sqrtd);
+    }
+}
+
+/// Real roots of `x³ + a x² + b x + c`, after Cardano
+fn solve_cubic([a, b, c]: [f64; 3]) -> Roots {
+    let mut roots = Roots::default();
+
+    // Substitute x = y - a/3 to get y³ + 3 p y + 2 q
+    let sq_a = a * a;
+    let p = (b - sq_a / 3.0) / 3.0;
+    let q = (2.0 / 27.0 * a * sq_a - a * b / 3.0 + c) / 2.0;
+    let cb_p = p * p * p;
+    let discriminant = q * q + cb_p;
+
+    if discriminant.abs() < EPSILON {
+        if q.abs() < EPSILON {
+            // One triple root
+            roots.push(0.0);
+        } else {
+            // One single and one double root
+            let u = (-q).cbrt();
+            roots.push(2.0 * u);
+            roots.push(-u);
+        }
+    } else if discriminant < 0.0 {
+        // Three real roots
+        let phi = (-q / (-cb_p).sqrt()).clamp(-1.0, 1.0).acos() / 3.0;
+        let t = 2.0 * (-p).sqrt();
+        roots.push(t * phi.cos());
+        roots.push(-t * (phi + PI / 3.0).cos());
+        roots.push(-t * (phi - PI / 3.0).cos());
+    } else {
+        // One real root
+        let sqrtd = discriminant.sqrt();
+        roots.push((sqrtd - q).cbrt() - (sqrtd + q).cbrt());
+    }
+
+    for x in &mut roots.values[..roots.len] {
+        *x -= a / 3.0;
+    }
+    roots
+}
+
+/// Real roots of `x⁴ + a x³ + b x² + c x + d`, after Ferrari, each refined
+/// with Newton's method
+fn solve_quartic([a, b, c, d]: [f64; 4]) -> Roots {
+    let mut roots = Roots::default();
+
+    // Substitute x = y - a/4 to get y⁴ + p y² + q y + r
+    let sq_a = a * a;
+    let p = -3.0 / 8.0 * sq_a + b;
+    let q = sq_a * a / 8.0 - a * b / 2.0 + c;
+    let r = -3.0 / 256.0 * sq_a * sq_a + sq_a * b / 16.0 - a * c / 4.0 + d;
+
+    if r.abs() < EPSILON {
+        // y (y³ + p y + q) = 0
+        roots.push(0.0);
+        for &y in solve_cubic([0.0, p, q]).as_slice() {
+            roots.push(y);
+        }
+    } else {
+        // Any root of the resolvent cubic splits the quartic into two
+        // quadratics
+        let z = solve_cubic([-p / 2.0, -r, r * p / 2.0 - q * q / 8.0]).values[0];
+
+        let u = z * z - r;
+        let v = 2.0 * z - p;
+        let u = if u.abs() < EPSILON {
+            0.0
+        } else if u > 0.0 {
+            u.sqrt()
+        } else {
+            return roots;
+        };
+        let v = if v.abs() < EPSILON {
+            0.0
+        } else if v > 0.0 {
+            v.sqrt()
+        } else {
+            return roots;
+        };
+
+        let v = if q < 0.0 { -v } else { v };
+        solve_normalized_quadratic(v, z - u, &mut roots);
+        solve_normalized_quadratic(-v, z + u, &mut roots);
+    }
+
+    let polynomial = |x: f64| (((x + a) * x + b) * x + c) * x + d;
+    let derivative = |x: f64| ((4.0 * x + 3.0 * a) * x + 2.0 * b) * x + c;
+    for x in &mut roots.values[..roots.len] {
+        *x -= a / 4.0;
+        for _ in 0..2 {
+            // Near double roots the slope vanishes, so only keep steps that
+            // actually improve the root
+            let step = *x - polynomial(*x) / derivative(*x);
+            if polynomial(step).abs() < polynomial(*x).abs() {
+                *x = step;
+            }
+        }
+    }
+    roots
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::{material::Lambertian, vec3::Color};
+
+    fn torus() -> Torus<Lambertian> {
+        Torus::new(
+            Point3::new(0, 0, 0),
+            Vec3::new(0, 1, 0),
+            1.0,
+            0.25,
+            Lambertian::new(Color::new(0.5, 0.5, 0.5)),
+        )
+    }
+
+    fn ray(origin: Point3, direction: Vec3) -> Ray {
+        Ray {
+            origin,
+            direction,
+            time: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_quartic() {
+        // (x - 1)(x + 2)(x - 3)(x - 0.5)
+        let mut roots = solve_quartic([-2.5, -4.0, 8.5, -3.0]).as_slice().to_vec();
+        roots.sort_by(f64::total_cmp);
+        assert_eq!(4, roots.len());
+        for (expected, actual) in [-2.0, 0.5, 1.0, 3.0].iter().zip(&roots) {
+            assert!((expected - actual).abs() < 1e-12, "{:?}", roots);
+        }
+
+        // x⁴ + 1 has no real roots
+        assert!(solve_quartic([0.0, 0.0, 0.0, 1.0]).as_slice().is_empty());
+    }
+
+    #[test]
+    fn test_hit() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let torus = torus();
+
+        // Through the tube from the outside, scaled direction
+        let hit = torus
+            .hit(
+                ray(Point3::new(5, 0, 0), Vec3::new(-2, 0, 0)),
+                0.0,
+                f64::INFINITY,
+                &mut rng,
+            )
+            .unwrap();
+        assert!((hit.t - 1.875).abs() < 1e-9);
+        assert!((hit.normal - Vec3::new(1, 0, 0)).near_zero());
+        assert!(hit.front_face);
+        assert!(hit.uv.1.abs() < 1e-9);
+
+        // Down through the hole
+        assert!(torus
+            .hit(
+                ray(Point3::new(0, 5, 0), Vec3::new(0, -1, 0)),
+                0.0,
+                f64::INFINITY,
+                &mut rng
+            )
+            .is_none());
+
+        // Down onto the top of the tube
+        let hit = torus
+            .hit(
+                ray(Point3::new(0, 5, 1), Vec3::new(0, -1, 0)),
+                0.0,
+                f64::INFINITY,
+                &mut rng,
+            )
+            .unwrap();
+        assert!((hit.t - 4.75).abs() < 1e-9);
+        assert!((hit.normal - Vec3::new(0, 1, 0)).near_zero());
+
+        let bbox = torus.bounding_box().unwrap();
+        assert!((bbox.minimum - Point3::new(-1.25, -0.25, -1.25)).near_zero());
+        assert!((bbox.maximum - Point3::new(1.25, 0.25, 1.25)).near_zero());
+    }
+
+    #[test]
+    fn test_far_away() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let torus = torus();
+        let hit = torus
+            .hit(
+                ray(Point3::new(1e4, 0, 0.5), Vec3::new(-1, 0, 0)),
+                0.0,
+                f64::INFINITY,
+                &mut rng,
+            )
+            .unwrap();
+        let x = (1.25f64.powi(2) - 0.25).sqrt();
+        assert!(
+            (hit.p - Point3::new(x, 0, 0.5)).length() < 1e-9,
+            "{:?}",
+            hit.p
+        );
+    }
+
+    #[test]
+    fn test_grazing() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let torus = torus();
+        // Tangent to the top of the tube, and to the outer rim
+        for (origin, offset) in [
+            (Point3::new(-5, 0.25, 1), Vec3::new(0, 1, 0)),
+            (Point3::new(-5, 0, 1.25), Vec3::new(0, 0, 1)),
+        ] {
+            let mut graze = |e: f64| {
+                torus.hit(
+                    ray(origin + e * offset, Vec3::new(1, 0, 0)),
+                    0.0,
+                    f64::INFINITY,
+                    &mut rng,
+                )
+            };
+            assert!(graze(-1e-6).is_some(), "{:?}", origin);
+            assert!(graze(1e-6).is_none(), "{:?}", origin);
+        }
+    }
+
+    #[test]
+    fn test_inside() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        // From the middle of the tube
+        let torus = torus();
+        let hit = torus
+            .hit(
+                ray(Point3::new(1, 0, 0), Vec3::new(1, 0, 0)),
+                0.0,
+                f64::INFINITY,
+                &mut rng,
+            )
+            .unwrap();
+        assert!((hit.t - 0.25).abs() < 1e-9);
+        assert!(!hit.front_face);
+        assert!((hit.normal - Vec3::new(-1, 0, 0)).near_zero());
+    }
+}
@@ -0,0 +1,101 @@
+use rand::prelude::SmallRng;
+
+use crate::{hittable::HitRecord, ray::Ray, transform::Transform};
+
+use super::{Aabb, Hittable};
+
+/// An object placed in the world by a transformation. Wrapping an
+/// `Arc<H>` allows many instances to share the same geometry.
+pub struct Transformed<H: Hittable> {
+    object: H,
+    /// From object to world space
+    transform: Transform,
+    bbox: Option<Aabb>,
+}
+
+impl<H: Hittable> Transformed<H> {
+    pub fn new(object: H, transform: Transform) -> Self {
+        let bbox = object
+            .bounding_box()
+            .map(|bbox| transform.apply_bounds(&bbox));
+        Self {
+            object,
+            transform,
+            bbox,
+        }
+    }
+}
+
+impl<H: Hittable> Hittable for Transformed<H> {
+    fn hit(&self, ray: Ray, t_min: f64, t_max: f64, rng: &mut SmallRng) -> Option<HitRecord<'_>> {
+        // The direction is not normalized, so `t` is the same in both spaces
+        let object_ray = self.transform.inverse().apply_ray(ray);
+        let mut hit = self.object.hit(object_ray, t_min, t_max, rng)?;
+
+        hit.p = self.transform.apply_point(hit.p);
+        hit.normal = self.transform.apply_normal(hit.normal).unit_vector();
+        hit.geometric_normal = self
+            .transform
+            .apply_normal(hit.geometric_normal)
+            .unit_vector();
+
+        Some(hit)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.bbox
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::{
+        hittable::Sphere,
+        material::Lambertian,
+        vec3::{Color, Point3, Vec3},
+    };
+
+    #[test]
+    fn test_instances() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let sphere = Arc::new(Sphere::new(
+            Point3::new(0, 0, 0),
+            1.0,
+            Lambertian::new(Color::new(0.5, 0.5, 0.5)),
+        ));
+        let moved = Transformed::new(
+            Arc::clone(&sphere),
+            Transform::translate(Vec3::new(0, 0, -5)),
+        );
+        let squashed = Transformed::new(sphere, Transform::scale(Vec3::new(1, 0.5, 1)));
+
+        let ray = Ray {
+            origin: Point3::new(0, 0, 0),
+            direction: Vec3::new(0, 0, -2),
+            time: 0.0,
+        };
+        let hit = moved.hit(ray, 0.0, f64::INFINITY, &mut rng).unwrap();
+        assert_eq!(2.0, hit.t);
+        assert_eq!(Point3::new(0, 0, -4), hit.p);
+        assert_eq!(Vec3::new(0, 0, 1), hit.normal);
+
+        let ray = Ray {
+            origin: Point3::new(0, 2, 0),
+            direction: Vec3::new(0, -1, 0),
+            time: 0.0,
+        };
+        let hit = squashed.hit(ray, 0.0, f64::INFINITY, &mut rng).unwrap();
+        assert_eq!(1.5, hit.t);
+        assert_eq!(Vec3::new(0, 1, 0), hit.normal);
+        assert!(hit.front_face);
+
+        let bbox = squashed.bounding_box().unwrap();
+        assert_eq!(Point3::new(-1, -0.5, -1), bbox.minimum);
+        assert_eq!(Point3::new(1, 0.5, 1), bbox.maximum);
+    }
+}
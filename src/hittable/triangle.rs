@@ -0,0 +1,344 @@
+use rand::{prelude::SmallRng, Rng};
+
+use crate::{
+    hittable::HitRecord,
+    material::Material,
+    ray::Ray,
+    vec3::{Point3, Vec3},
+};
+
+use super::{Aabb, Hittable};
+
+/// Rays closer to parallel to the triangle plane than this are ignored
+const EPSILON: f64 = 1e-12;
+
+pub struct Triangle<M: Material> {
+    pub vertices: [Point3; 3],
+    /// Optional per-vertex normals for smooth shading
+    pub normals: Option<[Vec3; 3]>,
+    /// Optional per-vertex texture coordinates
+    pub uvs: Option<[(f64, f64); 3]>,
+    pub material: M,
+}
+
+impl<M: Material> Triangle<M> {
+    pub fn new(a: Point3, b: Point3, c: Point3, material: M) -> Self {
+        Self {
+            vertices: [a, b, c],
+            normals: None,
+            uvs: None,
+            material,
+        }
+    }
+
+    /// Interpolate the given vertex normals across the face instead of using
+    /// the flat face normal for shading.
+    pub fn with_normals(mut self, normals: [Vec3; 3]) -> Self {
+        self.normals = Some(normals);
+        self
+    }
+
+    /// Interpolate the given texture coordinates across the face. Without
+    /// them, the barycentric coordinates are used.
+    pub fn with_uvs(mut self, uvs: [(f64, f64); 3]) -> Self {
+        self.uvs = Some(uvs);
+        self
+    }
+
+    /// Normal of the plane the triangle lies in, following the right-hand
+    /// rule for the vertex order.
+    pub fn face_normal(&self) -> Vec3 {
+        let [a, b, c] = self.vertices;
+        (b - a).cross(c - a).unit_vector()
+    }
+}
+
+impl<M: Material> Hittable for Triangle<M> {
+    fn hit(&self, ray: Ray, t_min: f64, t_max: f64, _rng: &mut SmallRng) -> Option<HitRecord<'_>> {
+        hit_triangle(
+            self.vertices,
+            self.normals,
+            self.uvs,
+            &self.material,
+            ray,
+            t_min,
+            t_max,
+        )
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(triangle_bounds(self.vertices))
+    }
+
+    /// Density of choosing `direction` from `origin`, when picking points
+    /// evenly over the area
+    fn pdf_value(&self, origin: Point3, direction: Vec3, rng: &mut SmallRng) -> f64 {
+        let ray = Ray {
+            origin,
+            direction,
+            time: 0.0,
+        };
+        let Some(hit) = self.hit(ray, 0.001, f64::INFINITY, rng) else {
+            return 0.0;
+        };
+        let [a, b, c] = self.vertices;
+        let area = 0.5 * (b - a).cross(c - a).length();
+        let distance_squared = hit.t * hit.t * direction.length_squared();
+        let cosine = hit.geometric_normal.dot(&direction).abs() / direction.length();
+        distance_squared / (cosine * area)
+    }
+
+    /// Direction from `origin` to a random point of the triangle
+    fn sample_direction(&self, origin: Point3, rng: &mut SmallRng) -> Option<Vec3> {
+        let [a, b, c] = self.vertices;
+        let s = rng.gen::<f64>().sqrt();
+        let t = rng.gen::<f64>();
+        let p = a + s * (1.0 - t) * (b - a) + s * t * (c - a);
+        Some(p - origin)
+    }
+}
+
+/// Möller–Trumbore ray/triangle intersection, shared by `Triangle` and the
+/// faces of a `Mesh`.
+pub(super) fn hit_triangle(
+    vertices: [Point3; 3],
+    normals: Option<[Vec3; 3]>,
+    uvs: Option<[(f64, f64); 3]>,
+    material: &dyn Material,
+    ray: Ray,
+    t_min: f64,
+    t_max: f64,
+) -> Option<HitRecord<'_>> {
+    let [a, b, c] = vertices;
+    let edge1 = b - a;
+    let edge2 = c - a;
+
+    let pvec = ray.direction.cross(edge2);
+    let det = edge1.dot(&pvec);
+    if det.abs() < EPSILON {
+        // parallel to the triangle
+        return None;
+    }
+    let inv_det = 1.0 / det;
+
+    let tvec = ray.origin - a;
+    let u = tvec.dot(&pvec) * inv_det;
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+
+    let qvec = tvec.cross(edge1);
+    let v = ray.direction.dot(&qvec) * inv_det;
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+
+    let t = edge2.dot(&qvec) * inv_det;
+    if t < t_min || t_max < t {
+        return None;
+    }
+
+    // We have a hit
+    let mut hit = HitRecord {
+        t,
+        p: ray.at(t),
+        material,
+        normal: Vec3::default(),
+        geometric_normal: Vec3::default(),
+        front_face: true,
+        barycentric: (u, v),
+        uv: (u, v),
+    };
+    hit.set_face_normal(ray, edge1.cross(edge2).unit_vector());
+
+    if let Some([na, nb, nc]) = normals {
+        let shading_normal = ((1.0 - u - v) * na + u * nb + v * nc).unit_vector();
+        // Keep the shading normal on the same side as the geometric one
+        hit.normal = if shading_normal.dot(&hit.geometric_normal) < 0.0 {
+            -shading_normal
+        } else {
+            shading_normal
+        };
+    }
+
+    if let Some([ta, tb, tc]) = uvs {
+        let w = 1.0 - u - v;
+        hit.uv = (
+            w * ta.0 + u * tb.0 + v * tc.0,
+            w * ta.1 + u * tb.1 + v * tc.1,
+        );
+    }
+
+    Some(hit)
+}
+
+pub(super) fn triangle_bounds([a, b, c]: [Point3; 3]) -> Aabb {
+    // Pad the box so axis-aligned triangles don't produce a flat box
+    let padding = Vec3::new(1e-4, 1e-4, 1e-4);
+    let bbox = Aabb::new(a, a)
+        .surrounding(&Aabb::new(b, b))
+        .surrounding(&Aabb::new(c, c));
+    Aabb::new(bbox.minimum - padding, bbox.maximum + padding)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::{material::Lambertian, random_unit_vector, vec3::Color};
+
+    fn triangle() -> Triangle<Lambertian> {
+        Triangle::new(
+            Point3::new(0, 0, 0),
+            Point3::new(1, 0, 0),
+            Point3::new(0, 1, 0),
+            Lambertian::new(Color::new(0.5, 0.5, 0.5)),
+        )
+    }
+
+    fn ray(origin: Point3, direction: Vec3) -> Ray {
+        Ray {
+            origin,
+            direction,
+            time: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_hit() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let t = triangle();
+        let hit = t
+            .hit(
+                ray(Point3::new(0.25, 0.5, 1), Vec3::new(0, 0, -1)),
+                0.0,
+                f64::INFINITY,
+                &mut rng,
+            )
+            .unwrap();
+        assert_eq!(1.0, hit.t);
+        assert_eq!(Point3::new(0.25, 0.5, 0), hit.p);
+        assert_eq!(Vec3::new(0, 0, 1), hit.normal);
+        assert_eq!(Vec3::new(0, 0, 1), hit.geometric_normal);
+        assert_eq!((0.25, 0.5), hit.barycentric);
+        assert!(hit.front_face);
+    }
+
+    #[test]
+    fn test_uvs() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let ray = ray(Point3::new(0.25, 0.5, 1), Vec3::new(0, 0, -1));
+        let t = triangle();
+        assert_eq!(
+            (0.25, 0.5),
+            t.hit(ray, 0.0, f64::INFINITY, &mut rng).unwrap().uv
+        );
+
+        let t = triangle().with_uvs([(1.0, 1.0), (0.0, 1.0), (1.0, 0.0)]);
+        assert_eq!(
+            (0.75, 0.5),
+            t.hit(ray, 0.0, f64::INFINITY, &mut rng).unwrap().uv
+        );
+    }
+
+    #[test]
+    fn test_hit_back_face() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let t = triangle();
+        let hit = t
+            .hit(
+                ray(Point3::new(0.25, 0.25, -2), Vec3::new(0, 0, 1)),
+                0.0,
+                f64::INFINITY,
+                &mut rng,
+            )
+            .unwrap();
+        assert_eq!(2.0, hit.t);
+        assert_eq!(Vec3::new(0, 0, -1), hit.normal);
+        assert!(!hit.front_face);
+    }
+
+    #[test]
+    fn test_miss() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let t = triangle();
+        let outside = ray(Point3::new(0.75, 0.75, 1), Vec3::new(0, 0, -1));
+        assert!(t.hit(outside, 0.0, f64::INFINITY, &mut rng).is_none());
+
+        let parallel = ray(Point3::new(0.25, 0.25, 0), Vec3::new(1, 0, 0));
+        assert!(t.hit(parallel, 0.0, f64::INFINITY, &mut rng).is_none());
+
+        let behind = ray(Point3::new(0.25, 0.25, 1), Vec3::new(0, 0, 1));
+        assert!(t.hit(behind, 0.0, f64::INFINITY, &mut rng).is_none());
+
+        let too_far = ray(Point3::new(0.25, 0.25, 1), Vec3::new(0, 0, -1));
+        assert!(t.hit(too_far, 0.0, 0.5, &mut rng).is_none());
+    }
+
+    #[test]
+    fn test_smooth_normals() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let t = triangle().with_normals([
+            Vec3::new(0, 0, 1),
+            Vec3::new(1, 0, 1).unit_vector(),
+            Vec3::new(0, 1, 1).unit_vector(),
+        ]);
+
+        // At a vertex the shading normal is that vertex's normal
+        let hit = t
+            .hit(
+                ray(Point3::new(1, 0, 1), Vec3::new(0, 0, -1)),
+                0.0,
+                f64::INFINITY,
+                &mut rng,
+            )
+            .unwrap();
+        assert!((hit.normal - Vec3::new(1, 0, 1).unit_vector()).near_zero());
+        assert_eq!(Vec3::new(0, 0, 1), hit.geometric_normal);
+
+        // Seen from behind, both normals are flipped
+        let hit = t
+            .hit(
+                ray(Point3::new(0, 0, -1), Vec3::new(0, 0, 1)),
+                0.0,
+                f64::INFINITY,
+                &mut rng,
+            )
+            .unwrap();
+        assert_eq!(Vec3::new(0, 0, -1), hit.normal);
+        assert_eq!(Vec3::new(0, 0, -1), hit.geometric_normal);
+    }
+
+    #[test]
+    fn test_bounding_box() {
+        let bbox = triangle().bounding_box().unwrap();
+        assert!(bbox.minimum.z() < 0.0);
+        assert!(bbox.maximum.z() > 0.0);
+        assert_eq!(1.0001, bbox.maximum.x());
+    }
+
+    #[test]
+    fn test_sample() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let t = triangle();
+        let origin = Point3::new(0.2, 0.3, -0.5);
+        for _ in 0..100 {
+            let direction = t.sample_direction(origin, &mut rng).unwrap();
+            assert!(t.pdf_value(origin, direction, &mut rng) > 0.0);
+        }
+
+        // The density integrates to one over all directions
+        let n = 200_000;
+        let integral = (0..n)
+            .map(|_| {
+                let direction = random_unit_vector(&mut rng);
+                4.0 * PI * t.pdf_value(origin, direction, &mut rng)
+            })
+            .sum::<f64>()
+            / n as f64;
+        assert!((integral - 1.0).abs() < 0.02, "{}", integral);
+    }
+}
@@ -3,10 +3,20 @@ use std::f64::consts::PI;
 use rand::{prelude::SmallRng, Rng};
 use vec3::{Point3, Vec3};
 
+pub mod background;
 pub mod camera;
+pub mod density;
+pub mod distribution;
+pub mod film;
 pub mod hittable;
 pub mod material;
+pub mod obj;
+pub mod output;
 pub mod ray;
+pub mod renderer;
+pub mod scene;
+pub mod texture;
+pub mod transform;
 pub mod vec3;
 
 pub fn degrees_to_radians(degrees: f64) -> f64 {
@@ -29,3 +39,13 @@ pub fn random_in_unit_sphere(rng: &mut SmallRng) -> Point3 {
 pub fn random_unit_vector(rng: &mut SmallRng) -> Vec3 {
     random_in_unit_sphere(rng).unit_vector()
 }
+
+/// Random direction around the z axis, with a density of `cos(θ) / π` per
+/// unit of solid angle
+pub fn random_cosine_direction(rng: &mut SmallRng) -> Vec3 {
+    let r1: f64 = rng.gen();
+    let r2: f64 = rng.gen();
+    let phi = 2.0 * PI * r1;
+    let r = r2.sqrt();
+    Vec3(r * phi.cos(), r * phi.sin(), (1.0 - r2).sqrt())
+}
@@ -1,174 +1,167 @@
 use std::{
-    f64::INFINITY,
-    io::{stderr, Write},
+    io::{stderr, stdout, BufWriter, Write},
+    path::PathBuf,
     time::Instant,
 };
 
-use rand::{distributions::uniform, prelude::SmallRng, thread_rng, Rng, SeedableRng};
-use rayon::iter::{IntoParallelIterator, ParallelIterator};
+use clap::{Parser, ValueEnum};
+use rand::{prelude::SmallRng, thread_rng, Rng, SeedableRng};
 use raytracing::{
-    camera::Camera,
-    hittable::{Hittable, HittableList, Sphere},
-    material::{Dielectric, Lambertian, Metal, ScatterResult},
-    ray::Ray,
-    vec3::{Color, Point3, Vec3},
+    hittable::BvhNode,
+    output,
+    renderer::{RenderSettings, Renderer},
+    scene::{builtin, Scene},
 };
 
-fn ray_color(ray: Ray, world: &impl Hittable, depth: u8, rng: &mut SmallRng) -> Color {
-    if depth == 0 {
-        // exceeded the ray bounce limit, no light is gathered.
-        return Color::new(0, 0, 0);
-    }
-
-    if let Some(hit) = world.hit(ray, 0.001, INFINITY) {
-        return match hit.material.scatter(ray, &hit, rng) {
-            ScatterResult::Scattered(scattered, attenuation) => {
-                attenuation * ray_color(scattered, world, depth - 1, rng)
-            }
-            ScatterResult::Absorbed(_) => Color::new(0, 0, 0),
-        };
-    }
+/// Render a scene using path tracing, following "Ray Tracing in One Weekend".
+///
+/// Settings given on the command line override those of the scene file.
+#[derive(Parser)]
+#[command(version, about)]
+struct Args {
+    /// Scene file to render
+    #[arg(long, value_name = "FILE", conflicts_with = "builtin")]
+    scene: Option<PathBuf>,
+
+    /// Built-in scene to render if no scene file is given
+    #[arg(long, value_enum, default_value_t = Builtin::Random)]
+    builtin: Builtin,
+
+    /// Image width in pixels
+    #[arg(short = 'W', long, value_parser = clap::value_parser!(u32).range(1..))]
+    width: Option<u32>,
+
+    /// Image height in pixels
+    #[arg(short = 'H', long, value_parser = clap::value_parser!(u32).range(1..))]
+    height: Option<u32>,
+
+    /// Number of samples per pixel
+    #[arg(short, long, value_parser = clap::value_parser!(u32).range(1..))]
+    samples: Option<u32>,
+
+    /// Maximum number of bounces per ray
+    #[arg(short = 'd', long, value_parser = clap::value_parser!(u8).range(1..))]
+    max_depth: Option<u8>,
+
+    /// Number of bounces after which dim paths may be ended at random
+    #[arg(long)]
+    roulette_depth: Option<u8>,
+
+    /// Where to write the image. The format is chosen by the file extension:
+    /// png, ppm, or exr, hdr, pfm for linear high dynamic range output.
+    /// Defaults to writing a PPM to stdout.
+    #[arg(short, long, value_name = "FILE")]
+    output: Option<PathBuf>,
+
+    /// Seed for the random number generator, for reproducible renders
+    #[arg(long)]
+    seed: Option<u64>,
+
+    /// Number of render threads. Defaults to the number of CPUs.
+    #[arg(short = 'j', long)]
+    threads: Option<usize>,
+}
 
-    let unit_direction = ray.direction.unit_vector();
-    let t = 0.5 * (unit_direction.y() + 1.0);
-    (1.0 - t) * Color::new(1, 1, 1) + t * Color::new(0.5, 0.7, 1.0)
+#[derive(Clone, Copy, ValueEnum)]
+enum Builtin {
+    /// The final scene of "Ray Tracing in One Weekend"
+    Random,
+    /// Only the three large spheres of the final scene
+    ThreeSpheres,
 }
 
-fn random_scene(rng: &mut SmallRng, random_spheres: bool) -> impl Hittable {
-    let mut world: HittableList = vec![
-        // Floor
-        Box::new(Sphere::new(
-            Point3::new(0, -1000, 0),
-            1000.0,
-            Lambertian::new(Color::new(0.5, 0.5, 0.5)),
-        )),
-        // three spheres
-        Box::new(Sphere::new(Point3::new(0, 1, 0), 1.0, Dielectric::new(1.5))),
-        Box::new(Sphere::new(
-            Point3::new(-4, 1, 0),
-            1.0,
-            Lambertian::new(Color::new(0.4, 0.2, 0.1)),
-        )),
-        Box::new(Sphere::new(
-            Point3::new(4, 1, 0),
-            1.0,
-            Metal::new(Color::new(0.7, 0.6, 0.5), 0.0),
-        )),
-    ];
-
-    if random_spheres {
-        for a in -11..11 {
-            for b in -11..11 {
-                let (a, b) = (a as f64, b as f64);
-                let choose_mat: f64 = rng.gen();
-                let center =
-                    Point3::new(a + 0.9 * rng.gen::<f64>(), 0.2, b + 0.9 * rng.gen::<f64>());
-
-                if (center - Vec3::new(4, 0.2, 0)).length() > 0.9 {
-                    let sphere: Box<dyn Hittable + Sync> = match choose_mat {
-                        x if x < 0.8 => {
-                            // Diffuse
-                            let albedo = Color::new_random(rng) * Color::new_random(rng);
-                            Box::new(Sphere::new(center, 0.2, Lambertian::new(albedo)))
-                        }
-                        x if x < 0.95 => {
-                            // Metal
-                            let albedo = Color::new_random_range(rng, 0.5, 1.0);
-                            let fuzz = rng.gen_range(0.0..0.5);
-                            Box::new(Sphere::new(center, 0.2, Metal::new(albedo, fuzz)))
-                        }
-                        _ => {
-                            // Glass
-                            Box::new(Sphere::new(center, 0.2, Dielectric::new(1.5)))
-                        }
-                    };
-
-                    world.push(sphere);
-                }
-            }
-        }
+fn main() {
+    if let Err(err) = run(Args::parse()) {
+        eprintln!("error: {}", err);
+        std::process::exit(1);
     }
-
-    world
 }
 
-fn main() -> Result<(), std::io::Error> {
+fn run(args: Args) -> Result<(), Box<dyn std::error::Error>> {
     let start = Instant::now();
 
-    // Image
-    const ASPECT_RATIO: f64 = 16.0 / 9.0;
-    const IMAGE_WIDTH: u64 = 400;
-    const IMAGE_HEIGHT: u64 = (IMAGE_WIDTH as f64 / ASPECT_RATIO) as u64;
-    const SAMPLES_PER_PIXEL: i32 = 100;
-    const MAX_DEPTH: u8 = 50;
-
-    // World
-    let mut rng = SmallRng::from_entropy();
-    let world = random_scene(&mut rng, true);
-
-    // Camera
-    let lookfrom = Point3::new(13, 2, 3);
-    let lookat = Point3::new(0, 0, 0);
-    let vup = Vec3::new(0, 1, 0);
-    let focus_dist = 10.0;
-    let aperture = 0.1;
-
-    let camera = Camera::new(
-        lookfrom,
-        lookat,
-        vup,
-        20.0,
-        ASPECT_RATIO,
-        aperture,
-        focus_dist,
-    );
-
-    // Render
+    if let Some(threads) = args.threads {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build_global()?;
+    }
+
+    let seed = args.seed.unwrap_or_else(|| thread_rng().gen());
+    let mut rng = SmallRng::seed_from_u64(seed);
+    let mut scene = match &args.scene {
+        Some(path) => Scene::load(path)?,
+        None => match args.builtin {
+            Builtin::Random => builtin::random(&mut rng, RenderSettings::default()),
+            Builtin::ThreeSpheres => builtin::three_spheres(RenderSettings::default()),
+        },
+    };
+
+    // Command line settings take precedence over the scene file
+    let settings = &mut scene.settings;
+    if let Some(samples) = args.samples {
+        settings.samples_per_pixel = samples;
+    }
+    if let Some(max_depth) = args.max_depth {
+        settings.max_depth = max_depth;
+    }
+    if let Some(roulette_depth) = args.roulette_depth {
+        settings.roulette_depth = roulette_depth;
+    }
+    let (width, height) = match (args.width, args.height) {
+        (Some(width), Some(height)) => (width, height),
+        // Keep the aspect ratio if only one dimension is given
+        (Some(width), None) => (width, (width as f64 / settings.aspect_ratio()) as u32),
+        (None, Some(height)) => ((height as f64 * settings.aspect_ratio()) as u32, height),
+        (None, None) => (settings.width, settings.height),
+    };
+    if (width, height) != (settings.width, settings.height) {
+        // The derived dimension of a very narrow or flat image may round to 0
+        settings.width = width.max(1);
+        settings.height = height.max(1);
+        scene.camera.set_aspect_ratio(settings.aspect_ratio());
+    }
+
+    let world = BvhNode::new(scene.world);
+    let settings = scene.settings;
+
+    // Fail early on unsupported output formats
+    if let Some(path) = &args.output {
+        output::Format::from_path(path)?;
+    }
+
     let stderr = stderr();
-    let width = (IMAGE_WIDTH - 1) as f64;
-    let height = (IMAGE_HEIGHT - 1) as f64;
-    let samples = SAMPLES_PER_PIXEL as f64;
-    let distr = uniform::Uniform::new(0.0, 1.0);
-
-    println!("P3\n{} {}\n255", IMAGE_WIDTH, IMAGE_HEIGHT,);
-
-    for y in (0..IMAGE_HEIGHT).rev() {
-        write!(
-            &stderr,
-            "\x1B[2K\rScanlines remaining: {} of {}",
-            y, IMAGE_HEIGHT
-        )?;
-
-        let line: String = (0..IMAGE_WIDTH)
-            .into_par_iter()
-            .map(|x| {
-                let (i, j) = (x as f64, y as f64);
-
-                let color = (0..SAMPLES_PER_PIXEL)
-                    .into_par_iter()
-                    .map(|_| {
-                        let mut rng = SmallRng::from_rng(thread_rng()).unwrap();
-                        let u = (i + rng.sample(distr)) / width;
-                        let v = (j + rng.sample(distr)) / height;
-                        let ray = camera.get_ray(u, v, &mut rng);
-                        ray_color(ray, &world, MAX_DEPTH, &mut rng)
-                    })
-                    .sum::<Color>()
-                    / samples;
-
-                color.to_string()
-            })
-            .collect::<Vec<String>>()
-            .join("\n");
-
-        println!("{}", &line);
+    let film = Renderer::new(settings)
+        .seed(seed)
+        .background(scene.background.as_ref())
+        .lights(&scene.lights)
+        .on_progress(|progress| {
+            // Progress output is best effort
+            let _ = write!(
+                &stderr,
+                "\x1B[2K\rScanlines remaining: {} of {}",
+                progress.rows_total - progress.rows_done,
+                progress.rows_total
+            );
+        })
+        .render(&world, &scene.camera);
+
+    let image = film.image();
+    match &args.output {
+        Some(path) => output::save(&image, path)?,
+        None => {
+            let mut out = BufWriter::new(stdout().lock());
+            output::write_ppm(&image, &mut out)?;
+            out.flush()?;
+        }
     }
 
-    let secs = start.elapsed().as_secs() as u64;
+    let secs = start.elapsed().as_secs_f64();
     write!(
         &stderr,
-        "\nDone in {}s ({}px/s)\n",
+        "\nDone in {:.1}s ({:.0}px/s)\n",
         secs,
-        IMAGE_WIDTH * IMAGE_HEIGHT / secs
-    )
+        settings.width as f64 * settings.height as f64 / secs
+    )?;
+
+    Ok(())
 }
@@ -1,4 +1,10 @@
-use crate::{hittable::HitRecord, ray::Ray, vec3::Color};
+use std::sync::Arc;
+
+use crate::{
+    hittable::HitRecord,
+    ray::Ray,
+    vec3::{Color, Vec3},
+};
 
 mod lambertian;
 pub use lambertian::*;
@@ -6,15 +12,70 @@ mod metal;
 pub use metal::*;
 mod dielectric;
 pub use dielectric::*;
+mod diffuse_light;
+pub use diffuse_light::*;
+mod henyey_greenstein;
+pub use henyey_greenstein::*;
+mod isotropic;
+pub use isotropic::*;
 use rand::prelude::SmallRng;
 
-pub enum ScatterResult {
-    Absorbed(Ray),
-    Scattered(Ray, Color),
+/// A direction chosen by a material, with what the integrator needs to
+/// weight the light arriving from it
+pub enum Sample {
+    /// Drawn with density `pdf` per unit of solid angle, from a distribution
+    /// that the material's `eval` and `pdf` describe
+    Scattered {
+        ray: Ray,
+        pdf: f64,
+    },
+    /// Drawn from a distribution without a density, like a mirror's, so
+    /// only `attenuation` is known
+    Specular {
+        ray: Ray,
+        attenuation: Color,
+    },
+    Absorbed,
 }
 
 pub trait Material {
-    /// 1. Produce a scattered ray (or say it absorbed the incident ray).
-    /// 2. If scattered, say how much the ray should be attenuated.
-    fn scatter(&self, ray: Ray, rec: &HitRecord, rng: &mut SmallRng) -> ScatterResult;
+    /// Draws a direction to continue `ray` in, or says the material absorbed
+    /// it
+    fn sample(&self, ray: Ray, rec: &HitRecord, rng: &mut SmallRng) -> Sample;
+
+    /// Light emitted by the surface towards the origin of `ray`
+    fn emitted(&self, _ray: Ray, _rec: &HitRecord) -> Color {
+        Color::new(0, 0, 0)
+    }
+
+    /// Fraction of the light arriving from `direction` that is scattered back
+    /// along `ray`, per unit of solid angle. For surfaces this is the BSDF
+    /// times the cosine with the normal.
+    fn eval(&self, _ray: Ray, _rec: &HitRecord, _direction: Vec3) -> Color {
+        Color::new(0, 0, 0)
+    }
+
+    /// Density per unit of solid angle with which `sample` draws `direction`
+    fn pdf(&self, _ray: Ray, _rec: &HitRecord, _direction: Vec3) -> f64 {
+        0.0
+    }
+}
+
+/// Allows one material to be shared between many objects
+impl<M: Material + ?Sized> Material for Arc<M> {
+    fn sample(&self, ray: Ray, rec: &HitRecord, rng: &mut SmallRng) -> Sample {
+        (**self).sample(ray, rec, rng)
+    }
+
+    fn emitted(&self, ray: Ray, rec: &HitRecord) -> Color {
+        (**self).emitted(ray, rec)
+    }
+
+    fn eval(&self, ray: Ray, rec: &HitRecord, direction: Vec3) -> Color {
+        (**self).eval(ray, rec, direction)
+    }
+
+    fn pdf(&self, ray: Ray, rec: &HitRecord, direction: Vec3) -> f64 {
+        (**self).pdf(ray, rec, direction)
+    }
 }
@@ -2,7 +2,7 @@ use rand::{prelude::SmallRng, Rng};
 
 use crate::{hittable::HitRecord, ray::Ray, vec3::Color};
 
-use super::{Material, ScatterResult};
+use super::{Material, Sample};
 
 #[derive(Clone, Copy)]
 pub struct Dielectric {
@@ -17,7 +17,7 @@ impl Dielectric {
 }
 
 impl Material for Dielectric {
-    fn scatter(&self, ray: Ray, rec: &HitRecord, rng: &mut SmallRng) -> ScatterResult {
+    fn sample(&self, ray: Ray, rec: &HitRecord, rng: &mut SmallRng) -> Sample {
         let refraction_ratio = if rec.front_face {
             1.0 / self.ir
         } else {
@@ -34,13 +34,14 @@ impl Material for Dielectric {
             unit_direction.refract(&rec.normal, refraction_ratio)
         };
 
-        ScatterResult::Scattered(
-            Ray {
+        Sample::Specular {
+            ray: Ray {
                 origin: rec.p,
                 direction,
+                time: ray.time,
             },
-            Color::new(1, 1, 1),
-        )
+            attenuation: Color::new(1, 1, 1),
+        }
     }
 }
 
@@ -0,0 +1,27 @@
+use rand::prelude::SmallRng;
+
+use crate::{hittable::HitRecord, ray::Ray, vec3::Color};
+
+use super::{Material, Sample};
+
+/// Emits light evenly from both sides of a surface, without reflecting any
+#[derive(Clone, Copy)]
+pub struct DiffuseLight {
+    emit: Color,
+}
+
+impl DiffuseLight {
+    pub fn new(emit: Color) -> Self {
+        Self { emit }
+    }
+}
+
+impl Material for DiffuseLight {
+    fn sample(&self, _ray: Ray, _rec: &HitRecord, _rng: &mut SmallRng) -> Sample {
+        Sample::Absorbed
+    }
+
+    fn emitted(&self, _ray: Ray, _rec: &HitRecord) -> Color {
+        self.emit
+    }
+}
@@ -0,0 +1,114 @@
+use std::f64::consts::PI;
+
+use rand::{prelude::SmallRng, Rng};
+
+use crate::{
+    hittable::HitRecord,
+    ray::Ray,
+    texture::Texture,
+    vec3::{Color, Onb, Vec3},
+};
+
+use super::{Material, Sample};
+
+/// Phase function of a medium that scatters mostly forwards (`g > 0`), or
+/// mostly backwards (`g < 0`). With `g = 0` it is isotropic.
+#[derive(Clone, Copy)]
+pub struct HenyeyGreenstein<T: Texture = Color> {
+    albedo: T,
+    /// Mean cosine of the scattering angle, in `(-1, 1)`
+    g: f64,
+}
+
+impl<T: Texture> HenyeyGreenstein<T> {
+    pub fn new(albedo: T, g: f64) -> Self {
+        Self { albedo, g }
+    }
+}
+
+impl<T: Texture> HenyeyGreenstein<T> {
+    /// Direction scattered away from the one `ray` was travelling in
+    fn direction(&self, ray: Ray, rng: &mut SmallRng) -> Vec3 {
+        let cos_theta = sample_cos_theta(self.g, rng.gen());
+        let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+        let phi = 2.0 * PI * rng.gen::<f64>();
+        Onb::from_w(ray.direction).local(Vec3::new(
+            sin_theta * phi.cos(),
+            sin_theta * phi.sin(),
+            cos_theta,
+        ))
+    }
+}
+
+impl<T: Texture> Material for HenyeyGreenstein<T> {
+    fn sample(&self, ray: Ray, rec: &HitRecord, rng: &mut SmallRng) -> Sample {
+        let direction = self.direction(ray, rng);
+        Sample::Scattered {
+            ray: Ray {
+                origin: rec.p,
+                direction,
+                time: ray.time,
+            },
+            pdf: self.pdf(ray, rec, direction),
+        }
+    }
+
+    fn eval(&self, ray: Ray, rec: &HitRecord, direction: Vec3) -> Color {
+        self.albedo.value(rec.uv, rec.p) * self.pdf(ray, rec, direction)
+    }
+
+    fn pdf(&self, ray: Ray, _rec: &HitRecord, direction: Vec3) -> f64 {
+        let cos_theta = ray.direction.unit_vector().dot(&direction.unit_vector());
+        henyey_greenstein(self.g, cos_theta)
+    }
+}
+
+/// Density of scattering by an angle with cosine `cos_theta` from the
+/// direction of travel, per unit of solid angle
+pub fn henyey_greenstein(g: f64, cos_theta: f64) -> f64 {
+    let denom = 1.0 + g * g - 2.0 * g * cos_theta;
+    (1.0 - g * g) / (4.0 * PI * denom * denom.sqrt())
+}
+
+/// Inverts the cumulative distribution of `henyey_greenstein` for `u` in
+/// `[0, 1)`
+fn sample_cos_theta(g: f64, u: f64) -> f64 {
+    if g.abs() < 1e-3 {
+        return 1.0 - 2.0 * u;
+    }
+    let s = (1.0 - g * g) / (1.0 - g + 2.0 * g * u);
+    ((1.0 + g * g - s * s) / (2.0 * g)).clamp(-1.0, 1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use super::*;
+
+    #[test]
+    fn test_normalized() {
+        for g in [-0.7, 0.0, 0.3, 0.9] {
+            // Integrate over the sphere, in steps of cos(theta)
+            let n = 100_000;
+            let integral: f64 = (0..n)
+                .map(|i| {
+                    let cos_theta = -1.0 + 2.0 * (i as f64 + 0.5) / n as f64;
+                    2.0 * PI * henyey_greenstein(g, cos_theta) * 2.0 / n as f64
+                })
+                .sum();
+            assert!((integral - 1.0).abs() < 1e-3, "{}: {}", g, integral);
+        }
+    }
+
+    #[test]
+    fn test_sampling() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        for g in [-0.5, 0.0, 0.8] {
+            // The mean cosine is g
+            let n = 100_000;
+            let mean = (0..n).map(|_| sample_cos_theta(g, rng.gen())).sum::<f64>() / n as f64;
+            assert!((mean - g).abs() < 0.01, "{}: {}", g, mean);
+        }
+    }
+}
@@ -0,0 +1,47 @@
+use std::f64::consts::PI;
+
+use rand::prelude::SmallRng;
+
+use crate::{
+    hittable::HitRecord,
+    random_unit_vector,
+    ray::Ray,
+    texture::Texture,
+    vec3::{Color, Vec3},
+};
+
+use super::{Material, Sample};
+
+/// Phase function of a participating medium that scatters light equally in
+/// all directions
+#[derive(Clone, Copy)]
+pub struct Isotropic<T: Texture = Color> {
+    albedo: T,
+}
+
+impl<T: Texture> Isotropic<T> {
+    pub fn new(albedo: T) -> Self {
+        Self { albedo }
+    }
+}
+
+impl<T: Texture> Material for Isotropic<T> {
+    fn sample(&self, ray: Ray, rec: &HitRecord, rng: &mut SmallRng) -> Sample {
+        Sample::Scattered {
+            ray: Ray {
+                origin: rec.p,
+                direction: random_unit_vector(rng),
+                time: ray.time,
+            },
+            pdf: 1.0 / (4.0 * PI),
+        }
+    }
+
+    fn eval(&self, _ray: Ray, rec: &HitRecord, _direction: Vec3) -> Color {
+        self.albedo.value(rec.uv, rec.p) / (4.0 * PI)
+    }
+
+    fn pdf(&self, _ray: Ray, _rec: &HitRecord, _direction: Vec3) -> f64 {
+        1.0 / (4.0 * PI)
+    }
+}
@@ -1,33 +1,105 @@
+use std::f64::consts::PI;
+
 use rand::prelude::SmallRng;
 
-use crate::{hittable::HitRecord, random_unit_vector, ray::Ray, vec3::Color};
+use crate::{
+    hittable::HitRecord,
+    random_cosine_direction,
+    ray::Ray,
+    texture::Texture,
+    vec3::{Color, Onb, Vec3},
+};
 
-use super::{Material, ScatterResult};
+use super::{Material, Sample};
 
 #[derive(Clone, Copy)]
-pub struct Lambertian {
-    albedo: Color,
+pub struct Lambertian<T: Texture = Color> {
+    albedo: T,
 }
 
-impl Lambertian {
-    pub fn new(albedo: Color) -> Self {
+impl<T: Texture> Lambertian<T> {
+    pub fn new(albedo: T) -> Self {
         Self { albedo }
     }
-}
 
-impl Material for Lambertian {
-    fn scatter(&self, _ray: Ray, rec: &HitRecord, rng: &mut SmallRng) -> ScatterResult {
-        let scatter_direction = match rec.normal + random_unit_vector(rng) {
-            dir if dir.near_zero() => rec.normal,
-            dir => dir,
-        };
+    /// Cosine-weighted direction around the normal
+    fn direction(&self, rec: &HitRecord, rng: &mut SmallRng) -> Vec3 {
+        Onb::from_w(rec.normal).local(random_cosine_direction(rng))
+    }
+}
 
-        ScatterResult::Scattered(
-            Ray {
+impl<T: Texture> Material for Lambertian<T> {
+    fn sample(&self, ray: Ray, rec: &HitRecord, rng: &mut SmallRng) -> Sample {
+        let direction = self.direction(rec, rng);
+        Sample::Scattered {
+            ray: Ray {
                 origin: rec.p,
-                direction: scatter_direction,
+                direction,
+                time: ray.time,
             },
-            self.albedo,
-        )
+            pdf: self.pdf(ray, rec, direction),
+        }
+    }
+
+    fn eval(&self, ray: Ray, rec: &HitRecord, direction: Vec3) -> Color {
+        self.albedo.value(rec.uv, rec.p) * self.pdf(ray, rec, direction)
+    }
+
+    fn pdf(&self, _ray: Ray, rec: &HitRecord, direction: Vec3) -> f64 {
+        let cosine = rec.normal.dot(&direction.unit_vector());
+        cosine.max(0.0) / PI
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::vec3::Point3;
+
+    #[test]
+    fn test_sample() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let lambertian = Lambertian::new(Color::new(0.5, 0.25, 1));
+        let material: &dyn Material = &lambertian;
+        let rec = HitRecord {
+            p: Point3::new(0, 0, 0),
+            normal: Vec3::new(0, 1, 0),
+            geometric_normal: Vec3::new(0, 1, 0),
+            material,
+            t: 1.0,
+            front_face: true,
+            barycentric: (0.0, 0.0),
+            uv: (0.0, 0.0),
+        };
+        let ray = Ray {
+            origin: Point3::new(0, 1, 1),
+            direction: Vec3::new(0, -1, -1),
+            time: 0.0,
+        };
+
+        let n = 100_000;
+        let mut mean_cosine = 0.0;
+        for _ in 0..n {
+            let Sample::Scattered {
+                ray: scattered,
+                pdf,
+            } = lambertian.sample(ray, &rec, &mut rng)
+            else {
+                panic!("expected a scattered ray");
+            };
+            let cosine = scattered.direction.unit_vector().y();
+            assert!(cosine >= 0.0);
+            assert!((pdf - cosine / PI).abs() < 1e-9);
+            // The weight of each sample is the albedo
+            let weight = lambertian.eval(ray, &rec, scattered.direction) / pdf;
+            assert!((weight - Color::new(0.5, 0.25, 1)).near_zero());
+            mean_cosine += cosine / n as f64;
+        }
+        // The mean of cos(θ) under a cosine distribution is 2/3
+        assert!((mean_cosine - 2.0 / 3.0).abs() < 0.01, "{}", mean_cosine);
+
+        assert_eq!(0.0, lambertian.pdf(ray, &rec, Vec3::new(0, -1, 0)));
     }
 }
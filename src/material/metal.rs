@@ -1,32 +1,135 @@
+use std::f64::consts::PI;
+
 use rand::prelude::SmallRng;
 
-use crate::{hittable::HitRecord, random_in_unit_sphere, ray::Ray, vec3::Color};
+use crate::{
+    hittable::HitRecord,
+    random_in_unit_sphere,
+    ray::Ray,
+    texture::Texture,
+    vec3::{Color, Vec3},
+};
 
-use super::{Material, ScatterResult};
+use super::{Material, Sample};
 
 #[derive(Clone, Copy)]
-pub struct Metal {
-    albedo: Color,
+pub struct Metal<T: Texture = Color> {
+    albedo: T,
     fuzz: f64,
 }
 
-impl Metal {
-    pub fn new(albedo: Color, fuzz: f64) -> Self {
+impl<T: Texture> Metal<T> {
+    pub fn new(albedo: T, fuzz: f64) -> Self {
         Self { albedo, fuzz }
     }
 }
 
-impl Material for Metal {
-    fn scatter(&self, ray: Ray, rec: &HitRecord, rng: &mut SmallRng) -> ScatterResult {
+impl<T: Texture> Material for Metal<T> {
+    /// A polished metal is specular, a fuzzy one has a lobe that lights can
+    /// be sampled against
+    fn sample(&self, ray: Ray, rec: &HitRecord, rng: &mut SmallRng) -> Sample {
         let reflected = ray.direction.reflect(&rec.normal);
         let scattered = Ray {
             origin: rec.p,
             direction: reflected + self.fuzz * random_in_unit_sphere(rng),
+            time: ray.time,
         };
 
-        match scattered.direction.dot(&rec.normal) {
-            x if x > 0.0 => ScatterResult::Scattered(scattered, self.albedo),
-            _ => ScatterResult::Absorbed(scattered),
+        if scattered.direction.dot(&rec.normal) <= 0.0 {
+            Sample::Absorbed
+        } else if self.fuzz == 0.0 {
+            Sample::Specular {
+                ray: scattered,
+                attenuation: self.albedo.value(rec.uv, rec.p),
+            }
+        } else {
+            Sample::Scattered {
+                ray: scattered,
+                pdf: self.pdf(ray, rec, scattered.direction),
+            }
+        }
+    }
+
+    fn eval(&self, ray: Ray, rec: &HitRecord, direction: Vec3) -> Color {
+        if direction.dot(&rec.normal) <= 0.0 {
+            return Color::new(0, 0, 0);
+        }
+        self.albedo.value(rec.uv, rec.p) * self.pdf(ray, rec, direction)
+    }
+
+    /// The scattered direction points at a random point of a ball of radius
+    /// `fuzz` around the tip of the reflected direction. The density of a
+    /// direction is the volume of the ball along it, `∫ r² dr` over the
+    /// chord, divided by the volume of the ball.
+    fn pdf(&self, ray: Ray, rec: &HitRecord, direction: Vec3) -> f64 {
+        if self.fuzz == 0.0 {
+            return 0.0;
+        }
+        let center = ray.direction.reflect(&rec.normal);
+        let direction = direction.unit_vector();
+
+        let half_b = direction.dot(&center);
+        let discriminant = half_b * half_b - center.length_squared() + self.fuzz * self.fuzz;
+        if discriminant <= 0.0 {
+            return 0.0;
+        }
+        let sqrtd = discriminant.sqrt();
+        let (near, far) = ((half_b - sqrtd).max(0.0), half_b + sqrtd);
+        if far <= 0.0 {
+            return 0.0;
+        }
+        (far.powi(3) - near.powi(3)) / (4.0 * PI * self.fuzz.powi(3))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::{random_unit_vector, vec3::Point3};
+
+    #[test]
+    fn test_pdf() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let ray = Ray {
+            origin: Point3::new(-1, 1, 0),
+            direction: Vec3::new(1, -1, 0).unit_vector(),
+            time: 0.0,
+        };
+        for fuzz in [0.5, 1.0, 2.5] {
+            let metal = Metal::new(Color::new(0.8, 0.8, 0.8), fuzz);
+            let material: &dyn Material = &metal;
+            let rec = HitRecord {
+                p: Point3::new(0, 0, 0),
+                normal: Vec3::new(0, 1, 0),
+                geometric_normal: Vec3::new(0, 1, 0),
+                material,
+                t: 1.0,
+                front_face: true,
+                barycentric: (0.0, 0.0),
+                uv: (0.0, 0.0),
+            };
+
+            // The density integrates to one over all directions
+            let n = 200_000;
+            let integral = (0..n)
+                .map(|_| 4.0 * PI * metal.pdf(ray, &rec, random_unit_vector(&mut rng)))
+                .sum::<f64>()
+                / n as f64;
+            assert!((integral - 1.0).abs() < 0.02, "{}: {}", fuzz, integral);
+
+            // Sampled directions are weighted by the albedo
+            for _ in 0..100 {
+                if let Sample::Scattered {
+                    ray: scattered,
+                    pdf,
+                } = metal.sample(ray, &rec, &mut rng)
+                {
+                    let weight = metal.eval(ray, &rec, scattered.direction) / pdf;
+                    assert!((weight - Color::new(0.8, 0.8, 0.8)).near_zero());
+                }
+            }
         }
     }
 }
@@ -0,0 +1,395 @@
+//! Loader for Wavefront OBJ meshes and their MTL material libraries
+
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    hittable::{Mesh, MeshData, MeshFace, MeshGroup},
+    material::Lambertian,
+    texture,
+    vec3::{Color, Point3, Vec3},
+};
+
+pub mod mtl;
+
+#[derive(Debug)]
+pub enum Error {
+    Io {
+        path: PathBuf,
+        source: io::Error,
+    },
+    Parse {
+        path: PathBuf,
+        line: usize,
+        message: String,
+    },
+    Texture(texture::Error),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Io { path, source } => write!(f, "{}: {}", path.display(), source),
+            Error::Parse {
+                path,
+                line,
+                message,
+            } => write!(f, "{}:{}: {}", path.display(), line, message),
+            Error::Texture(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io { source, .. } => Some(source),
+            Error::Parse { .. } => None,
+            Error::Texture(err) => Some(err),
+        }
+    }
+}
+
+/// Load an OBJ file, and any MTL files it references, into a `Mesh`.
+pub fn load(path: impl AsRef<Path>) -> Result<Mesh, Error> {
+    Ok(Mesh::new(load_data(path)?))
+}
+
+/// Load an OBJ file, and any MTL files it references, without building the
+/// mesh yet.
+pub fn load_data(path: impl AsRef<Path>) -> Result<MeshData, Error> {
+    let read_file = |path: &Path| {
+        fs::read_to_string(path).map_err(|source| Error::Io {
+            path: path.to_path_buf(),
+            source,
+        })
+    };
+
+    let path = path.as_ref();
+    let source = read_file(path)?;
+    parse(&source, path, read_file)
+}
+
+/// Parse the contents of an OBJ file.
+///
+/// `path` is used in error messages and to resolve `mtllib` statements, which
+/// are read using `read_file`.
+///
+/// Polygons are triangulated as fans. Faces before the first `usemtl` get a
+/// neutral grey `Lambertian` material at index 0.
+pub fn parse(
+    source: &str,
+    path: &Path,
+    read_file: impl Fn(&Path) -> Result<String, Error>,
+) -> Result<MeshData, Error> {
+    let mut data = MeshData {
+        materials: vec![Box::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)))],
+        ..Default::default()
+    };
+    let mut material_names: HashMap<String, usize> = HashMap::new();
+    let mut material = 0;
+    let mut group: Option<String> = None;
+    let mut group_start = 0;
+
+    for (index, line) in source.lines().enumerate() {
+        let err = |message: String| Error::Parse {
+            path: path.to_path_buf(),
+            line: index + 1,
+            message,
+        };
+
+        let line = line.split('#').next().unwrap_or_default();
+        let mut tokens = line.split_whitespace();
+        let Some(keyword) = tokens.next() else {
+            continue;
+        };
+        let args: Vec<&str> = tokens.collect();
+
+        match keyword {
+            "v" => {
+                let v = parse_floats(&args, 3, 4).map_err(err)?;
+                data.positions.push(Point3::new(v[0], v[1], v[2]));
+            }
+            "vn" => {
+                let n = parse_floats(&args, 3, 3).map_err(err)?;
+                data.normals.push(Vec3::new(n[0], n[1], n[2]).unit_vector());
+            }
+            "vt" => {
+                let uv = parse_floats(&args, 1, 3).map_err(err)?;
+                data.uvs.push((uv[0], uv.get(1).copied().unwrap_or(0.0)));
+            }
+            "f" => {
+                if args.len() < 3 {
+                    return Err(err(format!(
+                        "face needs at least 3 vertices, got {}",
+                        args.len()
+                    )));
+                }
+                let vertices = args
+                    .iter()
+                    .map(|vertex| parse_vertex(vertex, &data))
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(err)?;
+
+                // Only use normals and UVs if every vertex has them
+                let has_normals = vertices.iter().all(|v| v.2.is_some());
+                let has_uvs = vertices.iter().all(|v| v.1.is_some());
+
+                for i in 1..vertices.len() - 1 {
+                    let triangle = [vertices[0], vertices[i], vertices[i + 1]];
+                    data.faces.push(MeshFace {
+                        positions: triangle.map(|v| v.0),
+                        normals: has_normals.then(|| triangle.map(|v| v.2.unwrap())),
+                        uvs: has_uvs.then(|| triangle.map(|v| v.1.unwrap())),
+                        material,
+                    });
+                }
+            }
+            "g" | "o" => {
+                if let Some(name) = group.take() {
+                    data.groups.push(MeshGroup {
+                        name,
+                        faces: group_start..data.faces.len(),
+                    });
+                }
+                group = Some(if args.is_empty() {
+                    "default".to_string()
+                } else {
+                    args.join(" ")
+                });
+                group_start = data.faces.len();
+            }
+            "usemtl" => {
+                let name = args.join(" ");
+                material = *material_names
+                    .get(&name)
+                    .ok_or_else(|| err(format!("unknown material `{}`", name)))?;
+            }
+            "mtllib" => {
+                if args.is_empty() {
+                    return Err(err("`mtllib` without a file name".to_string()));
+                }
+                let dir = path.parent().unwrap_or(Path::new(""));
+                for file in args {
+                    let mtl_path = dir.join(file);
+                    for mtl in mtl::parse(&read_file(&mtl_path)?, &mtl_path)? {
+                        material_names.insert(mtl.name.clone(), data.materials.len());
+                        data.materials
+                            .push(mtl.to_material().map_err(Error::Texture)?);
+                    }
+                }
+            }
+            // Smoothing groups, lines, free-form geometry etc. are ignored
+            _ => {}
+        }
+    }
+
+    if let Some(name) = group {
+        data.groups.push(MeshGroup {
+            name,
+            faces: group_start..data.faces.len(),
+        });
+    }
+
+    Ok(data)
+}
+
+/// Parse a finite number. `inf` and `nan` are rejected like any other typo.
+pub(crate) fn parse_f64(value: &str) -> Result<f64, String> {
+    value
+        .parse()
+        .ok()
+        .filter(|v: &f64| v.is_finite())
+        .ok_or_else(|| format!("invalid number `{}`", value))
+}
+
+fn parse_floats(args: &[&str], min: usize, max: usize) -> Result<Vec<f64>, String> {
+    if args.len() < min || args.len() > max {
+        let expected = if min == max {
+            min.to_string()
+        } else {
+            format!("{} to {}", min, max)
+        };
+        return Err(format!("expected {} values, got {}", expected, args.len()));
+    }
+    args.iter().map(|arg| parse_f64(arg)).collect()
+}
+
+/// Parse a face vertex of the form `v`, `v/vt`, `v//vn` or `v/vt/vn` into
+/// zero-based (position, uv, normal) indices.
+fn parse_vertex(
+    vertex: &str,
+    data: &MeshData,
+) -> Result<(usize, Option<usize>, Option<usize>), String> {
+    let mut parts = vertex.split('/');
+    let position = parse_index(parts.next().unwrap_or_default(), data.positions.len())?;
+    let uv = match parts.next() {
+        None | Some("") => None,
+        Some(index) => Some(parse_index(index, data.uvs.len())?),
+    };
+    let normal = match parts.next() {
+        None | Some("") => None,
+        Some(index) => Some(parse_index(index, data.normals.len())?),
+    };
+    if parts.next().is_some() {
+        return Err(format!("invalid face vertex `{}`", vertex));
+    }
+
+    Ok((position, uv, normal))
+}
+
+/// Resolve a one-based (or negative, relative to the end) OBJ index
+fn parse_index(index: &str, len: usize) -> Result<usize, String> {
+    let i: i64 = index
+        .parse()
+        .map_err(|_| format!("invalid index `{}`", index))?;
+    let resolved = match i {
+        i if i > 0 => i - 1,
+        i if i < 0 => len as i64 + i,
+        _ => return Err("index 0 is not valid, indices start at 1".to_string()),
+    };
+    if resolved < 0 || resolved >= len as i64 {
+        return Err(format!("index {} out of range ({} defined)", i, len));
+    }
+    Ok(resolved as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn no_files(path: &Path) -> Result<String, Error> {
+        Err(Error::Io {
+            path: path.to_path_buf(),
+            source: io::Error::from(io::ErrorKind::NotFound),
+        })
+    }
+
+    fn parse_str(source: &str) -> Result<MeshData, Error> {
+        parse(source, Path::new("model/test.obj"), no_files)
+    }
+
+    #[test]
+    fn test_parse() {
+        let data = parse_str(
+            "# a square\n\
+             v 0 0 0\n\
+             v 1 0 0\n\
+             v 1 1 0\n\
+             v 0 1 0 1.0\n\
+             vt 0 0\n\
+             vt 1 0\n\
+             vt 1 1\n\
+             vt 0 1\n\
+             vn 0 0 2\n\
+             g square\n\
+             f 1/1/1 2/2/1 3/3/1 4/4/1\n\
+             g tri\n\
+             f -4//-1 -3//-1 -2//-1\n\
+             f 1 2 4\n",
+        )
+        .unwrap();
+
+        assert_eq!(4, data.positions.len());
+        assert_eq!(vec![Vec3::new(0, 0, 1)], data.normals);
+        assert_eq!((1.0, 1.0), data.uvs[2]);
+
+        assert_eq!(4, data.faces.len());
+        assert_eq!(
+            MeshFace {
+                positions: [0, 2, 3],
+                normals: Some([0, 0, 0]),
+                uvs: Some([0, 2, 3]),
+                material: 0,
+            },
+            data.faces[1]
+        );
+        assert_eq!(
+            MeshFace {
+                positions: [0, 1, 2],
+                normals: Some([0, 0, 0]),
+                uvs: None,
+                material: 0,
+            },
+            data.faces[2]
+        );
+        assert_eq!(None, data.faces[3].normals);
+
+        assert_eq!(
+            vec![
+                MeshGroup {
+                    name: "square".to_string(),
+                    faces: 0..2
+                },
+                MeshGroup {
+                    name: "tri".to_string(),
+                    faces: 2..4
+                },
+            ],
+            data.groups
+        );
+    }
+
+    #[test]
+    fn test_materials() {
+        let read_file = |path: &Path| {
+            assert_eq!(Path::new("model/test.mtl"), path);
+            Ok("newmtl red\nKd 1 0 0\nnewmtl glass\nd 0.5\n".to_string())
+        };
+        let data = parse(
+            "mtllib test.mtl\n\
+             v 0 0 0\nv 1 0 0\nv 0 1 0\n\
+             f 1 2 3\n\
+             usemtl glass\n\
+             f 1 2 3\n\
+             usemtl red\n\
+             f 1 2 3\n",
+            Path::new("model/test.obj"),
+            read_file,
+        )
+        .unwrap();
+
+        assert_eq!(3, data.materials.len());
+        let materials: Vec<usize> = data.faces.iter().map(|f| f.material).collect();
+        assert_eq!(vec![0, 2, 1], materials);
+    }
+
+    #[test]
+    fn test_errors() {
+        let err = parse_str("v 0 0 0\nv 1 0\n").err().unwrap();
+        assert_eq!(
+            "model/test.obj:2: expected 3 to 4 values, got 2",
+            err.to_string()
+        );
+
+        let err = parse_str("v 0 0 0\nv 1 0 0\nv 0 1 0\n\nf 1 2 4\n")
+            .err()
+            .unwrap();
+        assert_eq!(
+            "model/test.obj:5: index 4 out of range (3 defined)",
+            err.to_string()
+        );
+
+        let err = parse_str("v 0 0 0\nf 1 1\n").err().unwrap();
+        assert_eq!(
+            "model/test.obj:2: face needs at least 3 vertices, got 2",
+            err.to_string()
+        );
+
+        let err = parse_str("v 0 0 nope\n").err().unwrap();
+        assert_eq!("model/test.obj:1: invalid number `nope`", err.to_string());
+
+        let err = parse_str("usemtl shiny\n").err().unwrap();
+        assert_eq!(
+            "model/test.obj:1: unknown material `shiny`",
+            err.to_string()
+        );
+
+        let err = parse_str("mtllib missing.mtl\n").err().unwrap();
+        assert!(matches!(err, Error::Io { path, .. } if path == Path::new("model/missing.mtl")));
+    }
+}
@@ -0,0 +1,298 @@
+use std::path::{Path, PathBuf};
+
+use crate::{
+    material::{Dielectric, Lambertian, Material, Metal},
+    texture::{self, ImageTexture},
+    vec3::Color,
+};
+
+use super::{parse_f64, Error};
+
+/// Material definition as read from an MTL file
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mtl {
+    pub name: String,
+    /// `Kd`
+    pub diffuse: Color,
+    /// `map_Kd`, resolved relative to the MTL file
+    pub diffuse_map: Option<PathBuf>,
+    /// `Ks`
+    pub specular: Color,
+    /// `Ns`, the Phong exponent
+    pub shininess: f64,
+    /// `Ni`
+    pub optical_density: Option<f64>,
+    /// `d`, or `1 - Tr`
+    pub dissolve: f64,
+    /// `illum`
+    pub illum: Option<u32>,
+}
+
+impl Mtl {
+    fn new(name: String) -> Self {
+        Self {
+            name,
+            diffuse: Color::new(0.8, 0.8, 0.8),
+            diffuse_map: None,
+            specular: Color::new(0, 0, 0),
+            shininess: 0.0,
+            optical_density: None,
+            dissolve: 1.0,
+            illum: None,
+        }
+    }
+
+    /// Map the MTL parameters onto the closest of our materials:
+    ///
+    /// - transparent materials (`d < 1` or a refracting `illum` model)
+    ///   become `Dielectric`,
+    /// - reflective materials (a reflecting `illum` model, or only a
+    ///   specular color) become `Metal`, with the fuzz derived from `Ns`,
+    /// - everything else becomes `Lambertian`, textured with the diffuse
+    ///   map if there is one.
+    pub fn to_material(&self) -> Result<Box<dyn Material + Send + Sync>, texture::Error> {
+        Ok(match self.kind() {
+            Kind::Dielectric(ir) => Box::new(Dielectric::new(ir)),
+            Kind::Metal(albedo, fuzz) => Box::new(Metal::new(albedo, fuzz)),
+            Kind::Lambertian(albedo) => match &self.diffuse_map {
+                Some(path) => Box::new(Lambertian::new(ImageTexture::load(path)?)),
+                None => Box::new(Lambertian::new(albedo)),
+            },
+        })
+    }
+
+    fn kind(&self) -> Kind {
+        let transparent = self.dissolve < 1.0 || matches!(self.illum, Some(4 | 6 | 7 | 9));
+        if transparent {
+            return Kind::Dielectric(self.optical_density.unwrap_or(1.5));
+        }
+
+        let has_specular = !self.specular.near_zero();
+        let reflective =
+            matches!(self.illum, Some(3 | 5 | 8)) || (has_specular && self.diffuse.near_zero());
+        if reflective {
+            let albedo = if has_specular {
+                self.specular
+            } else {
+                self.diffuse
+            };
+            // Rough approximation of the Phong exponent as a roughness
+            let fuzz = (2.0 / (self.shininess + 2.0)).sqrt().clamp(0.0, 1.0);
+            return Kind::Metal(albedo, fuzz);
+        }
+
+        Kind::Lambertian(self.diffuse)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Kind {
+    Lambertian(Color),
+    Metal(Color, f64),
+    Dielectric(f64),
+}
+
+/// Parse the contents of an MTL file. `path` is used in error messages and to
+/// resolve texture maps.
+pub fn parse(source: &str, path: &Path) -> Result<Vec<Mtl>, Error> {
+    let mut materials: Vec<Mtl> = vec![];
+
+    for (index, line) in source.lines().enumerate() {
+        let err = |message: String| Error::Parse {
+            path: path.to_path_buf(),
+            line: index + 1,
+            message,
+        };
+
+        let line = line.split('#').next().unwrap_or_default();
+        let mut tokens = line.split_whitespace();
+        let Some(keyword) = tokens.next() else {
+            continue;
+        };
+        let args: Vec<&str> = tokens.collect();
+
+        if keyword == "newmtl" {
+            if args.is_empty() {
+                return Err(err("`newmtl` without a name".to_string()));
+            }
+            materials.push(Mtl::new(args.join(" ")));
+            continue;
+        }
+
+        let Some(current) = materials.last_mut() else {
+            return Err(err(format!("`{}` before any `newmtl`", keyword)));
+        };
+
+        match keyword {
+            "Kd" => current.diffuse = parse_color(&args).map_err(err)?,
+            "map_Kd" => {
+                // Options like `-s 1 1 1` come before the file name
+                let file = args
+                    .last()
+                    .ok_or_else(|| err("`map_Kd` without a file name".to_string()))?;
+                let dir = path.parent().unwrap_or(Path::new(""));
+                current.diffuse_map = Some(dir.join(file));
+            }
+            "Ks" => current.specular = parse_color(&args).map_err(err)?,
+            "Ns" => {
+                let shininess = parse_scalar(&args).map_err(err)?;
+                if shininess < 0.0 {
+                    return Err(err(format!("negative shininess `{}`", shininess)));
+                }
+                current.shininess = shininess;
+            }
+            "Ni" => {
+                let optical_density = parse_scalar(&args).map_err(err)?;
+                if optical_density <= 0.0 {
+                    return Err(err(format!(
+                        "optical density `{}` must be positive",
+                        optical_density
+                    )));
+                }
+                current.optical_density = Some(optical_density);
+            }
+            "d" | "Tr" => {
+                let value = parse_scalar(&args).map_err(err)?;
+                if !(0.0..=1.0).contains(&value) {
+                    return Err(err(format!(
+                        "`{}` must be between 0 and 1, got `{}`",
+                        keyword, value
+                    )));
+                }
+                current.dissolve = if keyword == "d" { value } else { 1.0 - value };
+            }
+            "illum" => {
+                let illum = parse_scalar(&args).map_err(err)?;
+                if illum < 0.0 || illum.fract() != 0.0 {
+                    return Err(err(format!("invalid illumination model `{}`", illum)));
+                }
+                current.illum = Some(illum as u32);
+            }
+            // Ambient/emissive colors, other texture maps etc. are not supported
+            _ => {}
+        }
+    }
+
+    Ok(materials)
+}
+
+fn parse_scalar(args: &[&str]) -> Result<f64, String> {
+    match args {
+        [value] => parse_f64(value),
+        _ => Err(format!("expected 1 value, got {}", args.len())),
+    }
+}
+
+fn parse_color(args: &[&str]) -> Result<Color, String> {
+    match args {
+        // A single value is used for all three channels
+        [value] => {
+            let v = parse_f64(value)?;
+            Ok(Color::new(v, v, v))
+        }
+        [r, g, b] => Ok(Color::new(parse_f64(r)?, parse_f64(g)?, parse_f64(b)?)),
+        _ => Err(format!("expected 1 or 3 values, got {}", args.len())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_one(source: &str) -> Mtl {
+        parse(source, Path::new("test.mtl")).unwrap().remove(0)
+    }
+
+    #[test]
+    fn test_parse() {
+        let materials = parse(
+            "# comment\n\
+             newmtl red\n\
+             Kd 1 0 0\n\
+             map_Kd -s 2 2 1 textures/red.png\n\
+             Ks 0.5\n\
+             Ns 10 # trailing comment\n\
+             \n\
+             newmtl glass\n\
+             Ni 1.3\n\
+             Tr 0.9\n\
+             illum 7\n",
+            Path::new("model/test.mtl"),
+        )
+        .unwrap();
+
+        assert_eq!(2, materials.len());
+        assert_eq!("red", materials[0].name);
+        assert_eq!(Color::new(1, 0, 0), materials[0].diffuse);
+        assert_eq!(
+            Some(PathBuf::from("model/textures/red.png")),
+            materials[0].diffuse_map
+        );
+        assert_eq!(Color::new(0.5, 0.5, 0.5), materials[0].specular);
+        assert_eq!(10.0, materials[0].shininess);
+        assert_eq!("glass", materials[1].name);
+        assert_eq!(Some(1.3), materials[1].optical_density);
+        assert!((materials[1].dissolve - 0.1).abs() < 1e-9);
+        assert_eq!(Some(7), materials[1].illum);
+    }
+
+    #[test]
+    fn test_kind() {
+        assert_eq!(
+            Kind::Lambertian(Color::new(1, 0, 0)),
+            parse_one("newmtl a\nKd 1 0 0\nillum 2").kind()
+        );
+        assert_eq!(Kind::Dielectric(1.5), parse_one("newmtl a\nd 0.5").kind());
+        assert_eq!(
+            Kind::Dielectric(1.33),
+            parse_one("newmtl a\nNi 1.33\nillum 6").kind()
+        );
+        assert_eq!(
+            Kind::Metal(Color::new(0.9, 0.9, 0.9), (2.0f64 / 200.0).sqrt()),
+            parse_one("newmtl a\nKd 0\nKs 0.9\nNs 198").kind()
+        );
+        assert_eq!(
+            Kind::Metal(Color::new(0.2, 0.3, 0.4), 1.0),
+            parse_one("newmtl a\nKd 0.2 0.3 0.4\nillum 3").kind()
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let err = parse("newmtl a\nKd 1 x 0", Path::new("test.mtl")).unwrap_err();
+        assert_eq!("test.mtl:2: invalid number `x`", err.to_string());
+
+        let err = parse("Kd 1 1 1", Path::new("test.mtl")).unwrap_err();
+        assert_eq!("test.mtl:1: `Kd` before any `newmtl`", err.to_string());
+
+        let err = parse("newmtl a\n\nNs 1 2", Path::new("test.mtl")).unwrap_err();
+        assert_eq!("test.mtl:3: expected 1 value, got 2", err.to_string());
+
+        let err = parse("newmtl a\nNs -10", Path::new("test.mtl")).unwrap_err();
+        assert_eq!("test.mtl:2: negative shininess `-10`", err.to_string());
+
+        let err = parse("newmtl a\nNs nan", Path::new("test.mtl")).unwrap_err();
+        assert_eq!("test.mtl:2: invalid number `nan`", err.to_string());
+
+        let err = parse("newmtl a\nKd inf 0 0", Path::new("test.mtl")).unwrap_err();
+        assert_eq!("test.mtl:2: invalid number `inf`", err.to_string());
+
+        let err = parse("newmtl a\nNi 0", Path::new("test.mtl")).unwrap_err();
+        assert_eq!(
+            "test.mtl:2: optical density `0` must be positive",
+            err.to_string()
+        );
+
+        let err = parse("newmtl a\nd 1.5", Path::new("test.mtl")).unwrap_err();
+        assert_eq!(
+            "test.mtl:2: `d` must be between 0 and 1, got `1.5`",
+            err.to_string()
+        );
+
+        let err = parse("newmtl a\nTr -0.5", Path::new("test.mtl")).unwrap_err();
+        assert_eq!(
+            "test.mtl:2: `Tr` must be between 0 and 1, got `-0.5`",
+            err.to_string()
+        );
+    }
+}
@@ -0,0 +1,247 @@
+//! Writers for rendered images
+
+use std::{
+    fmt::Display,
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use crate::film::Image;
+
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    Image(image::ImageError),
+    UnknownFormat(PathBuf),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Io(err) => write!(f, "{}", err),
+            Error::Image(err) => write!(f, "{}", err),
+            Error::UnknownFormat(path) => write!(
+                f,
+                "{}: unknown image format, expected one of: {}",
+                path.display(),
+                Format::EXTENSIONS.join(", ")
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(err) => Some(err),
+            Error::Image(err) => Some(err),
+            Error::UnknownFormat(_) => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    /// ASCII Portable Pixmap (P3)
+    Ppm,
+    /// 8-bit PNG
+    Png,
+    /// OpenEXR with linear 32-bit float channels
+    Exr,
+    /// Radiance RGBE
+    Hdr,
+    /// Portable Float Map with linear 32-bit float channels
+    Pfm,
+}
+
+impl Format {
+    const EXTENSIONS: [&'static str; 5] = ["ppm", "png", "exr", "hdr", "pfm"];
+
+    /// Pick the format based on the file extension
+    pub fn from_path(path: &Path) -> Result<Self, Error> {
+        let extension = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| ext.to_ascii_lowercase());
+        match extension.as_deref() {
+            Some("ppm") => Ok(Format::Ppm),
+            Some("png") => Ok(Format::Png),
+            Some("exr") => Ok(Format::Exr),
+            Some("hdr") => Ok(Format::Hdr),
+            Some("pfm") => Ok(Format::Pfm),
+            _ => Err(Error::UnknownFormat(path.to_path_buf())),
+        }
+    }
+}
+
+/// Write `image` to `path`, in the format given by the file extension
+pub fn save(image: &Image, path: &Path) -> Result<(), Error> {
+    let format = Format::from_path(path)?;
+    match format {
+        Format::Ppm => {
+            let mut file = BufWriter::new(File::create(path)?);
+            write_ppm(image, &mut file)?;
+            file.flush()?;
+        }
+        Format::Png => {
+            let buf = image.pixels.iter().flat_map(|c| c.to_srgb8()).collect();
+            image::RgbImage::from_raw(image.width, image.height, buf)
+                .expect("pixel count must match the image size")
+                .save_with_format(path, image::ImageFormat::Png)
+                .map_err(Error::Image)?;
+        }
+        Format::Exr | Format::Hdr => {
+            // High dynamic range formats get the linear, unclamped values
+            let buf = image
+                .pixels
+                .iter()
+                .flat_map(|c| [c.x() as f32, c.y() as f32, c.z() as f32])
+                .collect();
+            let format = if format == Format::Exr {
+                image::ImageFormat::OpenExr
+            } else {
+                image::ImageFormat::Hdr
+            };
+            image::Rgb32FImage::from_raw(image.width, image.height, buf)
+                .expect("pixel count must match the image size")
+                .save_with_format(path, format)
+                .map_err(Error::Image)?;
+        }
+        Format::Pfm => {
+            let mut file = BufWriter::new(File::create(path)?);
+            write_pfm(image, &mut file)?;
+            file.flush()?;
+        }
+    }
+    Ok(())
+}
+
+/// Write `image` as an ASCII PPM
+pub fn write_ppm(image: &Image, mut out: impl Write) -> io::Result<()> {
+    writeln!(out, "P3\n{} {}\n255", image.width, image.height)?;
+    for color in &image.pixels {
+        writeln!(out, "{}", color)?;
+    }
+    Ok(())
+}
+
+/// Write `image` as a little-endian Portable Float Map with linear colors
+pub fn write_pfm(image: &Image, mut out: impl Write) -> io::Result<()> {
+    // A negative scale marks little-endian data
+    write!(out, "PF\n{} {}\n-1.0\n", image.width, image.height)?;
+    // Rows are stored from the bottom up
+    for row in image.pixels.chunks(image.width as usize).rev() {
+        for color in row {
+            for c in [color.x(), color.y(), color.z()] {
+                out.write_all(&(c as f32).to_le_bytes())?;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vec3::Color;
+
+    #[test]
+    fn test_from_path() {
+        assert_eq!(Format::Png, Format::from_path(Path::new("a.png")).unwrap());
+        assert_eq!(Format::Png, Format::from_path(Path::new("a.PNG")).unwrap());
+        assert_eq!(
+            Format::Ppm,
+            Format::from_path(Path::new("a/b.ppm")).unwrap()
+        );
+        assert_eq!(Format::Exr, Format::from_path(Path::new("a.exr")).unwrap());
+        assert_eq!(Format::Hdr, Format::from_path(Path::new("a.hdr")).unwrap());
+        assert_eq!(Format::Pfm, Format::from_path(Path::new("a.pfm")).unwrap());
+        assert!(Format::from_path(Path::new("a.gif")).is_err());
+        assert!(Format::from_path(Path::new("a")).is_err());
+    }
+
+    #[test]
+    fn test_write_ppm() {
+        let pixels = vec![
+            Color::new(0, 0, 0),
+            Color::new(1, 1, 1),
+            Color::new(0, 0.5, 1),
+            Color::new(0.25, 0.25, 0.25),
+        ];
+        let image = Image {
+            width: 2,
+            height: 2,
+            pixels,
+        };
+
+        let mut out = vec![];
+        write_ppm(&image, &mut out).unwrap();
+        assert_eq!(
+            "P3\n2 2\n255\n0 0 0\n255 255 255\n0 181 255\n128 128 128\n",
+            String::from_utf8(out).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_write_pfm() {
+        let pixels = vec![Color::new(0, 0.5, 1), Color::new(2, 4, 8)];
+        let image = Image {
+            width: 1,
+            height: 2,
+            pixels,
+        };
+
+        let mut out = vec![];
+        write_pfm(&image, &mut out).unwrap();
+
+        let header = b"PF\n1 2\n-1.0\n";
+        assert_eq!(header, &out[..header.len()]);
+        let values: Vec<f32> = out[header.len()..]
+            .chunks(4)
+            .map(|b| f32::from_le_bytes(b.try_into().unwrap()))
+            .collect();
+        // Bottom row first, values not clamped
+        assert_eq!(vec![2.0, 4.0, 8.0, 0.0, 0.5, 1.0], values);
+    }
+
+    #[test]
+    fn test_save_png() {
+        let pixels = vec![Color::new(0, 0.5, 1), Color::new(0.25, 0.001, 2)];
+        let image = Image {
+            width: 2,
+            height: 1,
+            pixels,
+        };
+        let path = std::env::temp_dir().join(format!("raytracing-{}.png", std::process::id()));
+        save(&image, &path).unwrap();
+
+        // sRGB encoded, clamped to white
+        let read = image::open(&path).unwrap().into_rgb8();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!([0, 188, 255], read.get_pixel(0, 0).0);
+        assert_eq!([137, 3, 255], read.get_pixel(1, 0).0);
+    }
+
+    #[test]
+    fn test_save_exr() {
+        let pixels = vec![Color::new(0, 0.5, 1), Color::new(2, 4, 8)];
+        let image = Image {
+            width: 2,
+            height: 1,
+            pixels,
+        };
+        let path = std::env::temp_dir().join(format!("raytracing-{}.exr", std::process::id()));
+        save(&image, &path).unwrap();
+
+        let read = image::open(&path).unwrap().into_rgb32f();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!([2.0, 4.0, 8.0], read.get_pixel(1, 0).0);
+    }
+}
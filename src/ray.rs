@@ -4,6 +4,8 @@ use crate::vec3::{Point3, Vec3};
 pub struct Ray {
     pub origin: Point3,
     pub direction: Vec3,
+    /// Moment the ray was sent, for motion blur
+    pub time: f64,
 }
 
 impl Ray {
@@ -21,6 +23,7 @@ mod tests {
         let ray = Ray {
             origin: Point3::new(0, 0, 0),
             direction: Vec3::new(1, 2, 3),
+            time: 0.0,
         };
         assert_eq!(Point3::new(1, 2, 3), ray.at(1.0));
         assert_eq!(Point3::new(2, 4, 6), ray.at(2.0));
@@ -28,6 +31,7 @@ mod tests {
         let ray = Ray {
             origin: Point3::new(1, 1, 1),
             direction: Vec3::new(1, 2, 3),
+            time: 0.0,
         };
         assert_eq!(Point3::new(2, 3, 4), ray.at(1.0));
         assert_eq!(Point3::new(3, 5, 7), ray.at(2.0));
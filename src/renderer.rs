@@ -0,0 +1,801 @@
+//! The render loop, turning a scene into a `Film`
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use rand::{distributions::Uniform, prelude::SmallRng, Rng, SeedableRng};
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+use serde::Deserialize;
+
+use crate::{
+    background::{Background, Gradient},
+    camera::Camera,
+    film::Film,
+    hittable::{HitRecord, Hittable, HittableList},
+    material::Sample,
+    ray::Ray,
+    vec3::{Color, Vec3},
+};
+
+/// Image settings for rendering a scene
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RenderSettings {
+    pub width: u32,
+    pub height: u32,
+    pub samples_per_pixel: u32,
+    /// Maximum number of bounces per ray
+    pub max_depth: u8,
+    /// Bounces after which paths carrying little light may be ended at
+    /// random (Russian roulette). At `max_depth` or above, paths always go
+    /// on until the limit.
+    pub roulette_depth: u8,
+    /// How direct light found by sampling lights and by sampling materials
+    /// is combined
+    pub heuristic: Heuristic,
+}
+
+impl Default for RenderSettings {
+    fn default() -> Self {
+        Self {
+            width: 400,
+            height: 225,
+            samples_per_pixel: 100,
+            max_depth: 50,
+            roulette_depth: 3,
+            heuristic: Heuristic::default(),
+        }
+    }
+}
+
+/// Weighting of samples that two strategies could have drawn, in multiple
+/// importance sampling. Each strategy gets more weight where its density is
+/// higher, so the combination is never much noisier than the better one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Heuristic {
+    /// In proportion to the densities
+    Balance,
+    /// In proportion to the squared densities, favoring the better strategy
+    /// more strongly
+    #[default]
+    Power,
+}
+
+impl Heuristic {
+    /// Weight of a sample drawn with density `pdf`, when the other strategy
+    /// would have drawn it with density `other`
+    pub fn weight(self, pdf: f64, other: f64) -> f64 {
+        match self {
+            Heuristic::Balance => pdf / (pdf + other),
+            Heuristic::Power => pdf * pdf / (pdf * pdf + other * other),
+        }
+    }
+}
+
+impl RenderSettings {
+    pub fn aspect_ratio(&self) -> f64 {
+        self.width as f64 / self.height as f64
+    }
+}
+
+const SKY: Gradient = Gradient::sky();
+static NO_LIGHTS: HittableList = Vec::new();
+
+/// How far a render has progressed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Progress {
+    pub rows_done: u32,
+    pub rows_total: u32,
+}
+
+/// Renders a world as seen by a camera.
+///
+/// ```
+/// use raytracing::{
+///     camera::Camera,
+///     hittable::{HittableList, Sphere},
+///     material::Lambertian,
+///     renderer::{RenderSettings, Renderer},
+///     vec3::{Color, Point3, Vec3},
+/// };
+///
+/// let settings = RenderSettings {
+///     width: 16,
+///     height: 9,
+///     samples_per_pixel: 4,
+///     max_depth: 10,
+///     ..Default::default()
+/// };
+/// let world: HittableList = vec![Box::new(Sphere::new(
+///     Point3::new(0, 0, -1),
+///     0.5,
+///     Lambertian::new(Color::new(0.5, 0.5, 0.5)),
+/// ))];
+/// let camera = Camera::new(
+///     Point3::new(0, 0, 0),
+///     Point3::new(0, 0, -1),
+///     Vec3::new(0, 1, 0),
+///     90.0,
+///     settings.aspect_ratio(),
+///     0.0,
+///     1.0,
+/// );
+///
+/// let film = Renderer::new(settings).seed(42).render(&world, &camera);
+/// assert_eq!(4, film.sample_count(0, 0));
+/// ```
+pub struct Renderer<'a> {
+    settings: RenderSettings,
+    seed: u64,
+    background: &'a (dyn Background + Sync),
+    lights: &'a (dyn Hittable + Sync),
+    on_progress: Option<Box<dyn FnMut(Progress) + 'a>>,
+    cancel: Option<&'a AtomicBool>,
+}
+
+impl<'a> Renderer<'a> {
+    pub fn new(settings: RenderSettings) -> Self {
+        Self {
+            settings,
+            seed: 0,
+            background: &SKY,
+            lights: &NO_LIGHTS,
+            on_progress: None,
+            cancel: None,
+        }
+    }
+
+    pub fn settings(&self) -> &RenderSettings {
+        &self.settings
+    }
+
+    /// Seed for the random number generator. Renders with the same seed and
+    /// settings produce the same image, regardless of the number of threads.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Color of rays leaving the scene. Defaults to `Gradient::sky()`.
+    pub fn background(mut self, background: &'a (dyn Background + Sync)) -> Self {
+        self.background = background;
+        self
+    }
+
+    /// Objects that emit light, sampled directly at every diffuse hit. They
+    /// must also be part of the world. Defaults to none.
+    pub fn lights(mut self, lights: &'a (dyn Hittable + Sync)) -> Self {
+        self.lights = lights;
+        self
+    }
+
+    /// Called on the rendering thread after every finished row
+    pub fn on_progress(mut self, on_progress: impl FnMut(Progress) + 'a) -> Self {
+        self.on_progress = Some(Box::new(on_progress));
+        self
+    }
+
+    /// Stop rendering as soon as `cancel` is set. The film returned by a
+    /// cancelled render only contains the rows finished until then.
+    pub fn cancel_flag(mut self, cancel: &'a AtomicBool) -> Self {
+        self.cancel = Some(cancel);
+        self
+    }
+
+    fn cancelled(&self) -> bool {
+        self.cancel.is_some_and(|c| c.load(Ordering::Relaxed))
+    }
+
+    pub fn render(&mut self, world: &(impl Hittable + Sync), camera: &Camera) -> Film {
+        let settings = self.settings;
+        let RenderSettings {
+            width: image_width,
+            height: image_height,
+            samples_per_pixel,
+            ..
+        } = settings;
+        let seed = self.seed;
+        let background = self.background;
+        let lights = self.lights;
+
+        let mut film = Film::new(image_width, image_height);
+        // Each sample falls somewhere inside its pixel's footprint
+        let width = image_width as f64;
+        let height = image_height as f64;
+        let distr = Uniform::new(0.0, 1.0);
+
+        for row in 0..image_height {
+            if self.cancelled() {
+                break;
+            }
+
+            // The film starts at the top, the camera's `v` at the bottom
+            let y = image_height - 1 - row;
+            let j = y as f64;
+            let line: Vec<Color> = (0..image_width)
+                .into_par_iter()
+                .map(|x| {
+                    let i = x as f64;
+                    let pixel = y as u64 * image_width as u64 + x as u64;
+
+                    (0..samples_per_pixel)
+                        .into_par_iter()
+                        .map(|s| {
+                            // Derive each sample's RNG from the seed, so results
+                            // don't depend on how rayon schedules the work.
+                            let sample = pixel * samples_per_pixel as u64 + s as u64;
+                            let mut rng = SmallRng::seed_from_u64(
+                                seed ^ sample.wrapping_mul(0x9E37_79B9_7F4A_7C15),
+                            );
+                            let u = (i + rng.sample(distr)) / width;
+                            let v = (j + rng.sample(distr)) / height;
+                            let ray = camera.get_ray(u, v, &mut rng);
+                            ray_color(ray, world, lights, background, &settings, &mut rng)
+                        })
+                        .sum::<Color>()
+                })
+                .collect();
+
+            for (x, sum) in line.into_iter().enumerate() {
+                film.add_samples(x as u32, row, sum, samples_per_pixel);
+            }
+
+            if let Some(on_progress) = &mut self.on_progress {
+                on_progress(Progress {
+                    rows_done: row + 1,
+                    rows_total: image_height,
+                });
+            }
+        }
+
+        film
+    }
+}
+
+/// Radiance arriving along `ray`, following it for at most
+/// `settings.max_depth` bounces. At every diffuse hit, light from `lights` and
+/// the background is also sampled directly, and weighted against the light
+/// found by the bounce with `settings.heuristic`.
+pub fn ray_color(
+    ray: Ray,
+    world: &impl Hittable,
+    lights: &dyn Hittable,
+    background: &dyn Background,
+    settings: &RenderSettings,
+    rng: &mut SmallRng,
+) -> Color {
+    let tracer = Tracer {
+        world,
+        lights,
+        background,
+        settings,
+    };
+    tracer.trace(ray, rng)
+}
+
+/// What rays are traced against, and how
+struct Tracer<'a, W: Hittable> {
+    world: &'a W,
+    lights: &'a dyn Hittable,
+    background: &'a dyn Background,
+    settings: &'a RenderSettings,
+}
+
+impl<W: Hittable> Tracer<'_, W> {
+    fn trace(&self, mut ray: Ray, rng: &mut SmallRng) -> Color {
+        let heuristic = self.settings.heuristic;
+        let mut color = Color::new(0, 0, 0);
+        // Fraction of the light arriving along `ray` that reaches the camera
+        let mut throughput = Color::new(1, 1, 1);
+        // Density with which the material of a diffuse hit chose `ray`
+        let mut bsdf_pdf = None;
+
+        for depth in 0..self.settings.max_depth {
+            let Some(hit) = self.world.hit(ray, 0.001, f64::INFINITY, rng) else {
+                let mut background = self.background.color(ray);
+                if let Some(pdf) = bsdf_pdf {
+                    background *= heuristic.weight(pdf, self.background.pdf(ray.direction));
+                }
+                return color + throughput * background;
+            };
+
+            let mut emitted = hit.material.emitted(ray, &hit);
+            if let Some(pdf) = bsdf_pdf {
+                if emitted != Color::new(0, 0, 0) {
+                    let light_pdf = self.lights.pdf_value(ray.origin, ray.direction, rng);
+                    emitted *= heuristic.weight(pdf, light_pdf);
+                }
+            }
+            color += throughput * emitted;
+
+            match hit.material.sample(ray, &hit, rng) {
+                Sample::Scattered {
+                    ray: scattered,
+                    pdf,
+                } if pdf > 0.0 => {
+                    color += throughput * self.direct_light(ray, &hit, rng);
+                    throughput *= hit.material.eval(ray, &hit, scattered.direction) / pdf;
+                    bsdf_pdf = Some(pdf);
+                    ray = scattered;
+                }
+                Sample::Specular {
+                    ray: scattered,
+                    attenuation,
+                } => {
+                    throughput *= attenuation;
+                    bsdf_pdf = None;
+                    ray = scattered;
+                }
+                Sample::Scattered { .. } | Sample::Absorbed => return color,
+            }
+
+            // End dim paths at random, and make up for it in the ones that
+            // go on, so the result is right on average
+            if depth + 1 >= self.settings.roulette_depth {
+                let survival = throughput
+                    .x()
+                    .max(throughput.y())
+                    .max(throughput.z())
+                    .min(1.0);
+                if rng.gen::<f64>() >= survival {
+                    return color;
+                }
+                throughput /= survival;
+            }
+        }
+
+        // exceeded the ray bounce limit, no more light is gathered.
+        color
+    }
+
+    /// Light reaching `hit` straight from one of the lights and from the
+    /// background, and scattered back along `ray`, with one shadow ray towards
+    /// each
+    fn direct_light(&self, ray: Ray, hit: &HitRecord, rng: &mut SmallRng) -> Color {
+        self.light_sample(ray, hit, rng) + self.background_sample(ray, hit, rng)
+    }
+
+    /// Light from a point chosen on `lights`, weighted against the material
+    /// finding the same light by scattering towards it
+    fn light_sample(&self, ray: Ray, hit: &HitRecord, rng: &mut SmallRng) -> Color {
+        let Some(direction) = self.lights.sample_direction(hit.p, rng) else {
+            return Color::new(0, 0, 0);
+        };
+        let pdf = self.lights.pdf_value(hit.p, direction, rng);
+        let f = hit.material.eval(ray, hit, direction);
+        if pdf <= 0.0 || f == Color::new(0, 0, 0) {
+            return Color::new(0, 0, 0);
+        }
+
+        // Whatever is hit first, whether a light or not
+        let shadow = shadow_ray(ray, hit, direction);
+        match self.world.hit(shadow, 0.001, f64::INFINITY, rng) {
+            Some(light) => {
+                let bsdf_pdf = hit.material.pdf(ray, hit, direction);
+                let weight = self.settings.heuristic.weight(pdf, bsdf_pdf);
+                weight * f * light.material.emitted(shadow, &light) / pdf
+            }
+            None => Color::new(0, 0, 0),
+        }
+    }
+
+    /// Light from a direction chosen by `Background::sample`, so bright parts
+    /// of the background such as a sun are found without waiting for a bounce
+    /// to escape towards them. Weighted against the material scattering into
+    /// the same direction, which `trace` weights the other way round.
+    fn background_sample(&self, ray: Ray, hit: &HitRecord, rng: &mut SmallRng) -> Color {
+        let Some(sample) = self.background.sample(rng) else {
+            return Color::new(0, 0, 0);
+        };
+        let f = hit.material.eval(ray, hit, sample.direction);
+        if sample.pdf <= 0.0 || f == Color::new(0, 0, 0) {
+            return Color::new(0, 0, 0);
+        }
+
+        let shadow = shadow_ray(ray, hit, sample.direction);
+        if self.world.hit(shadow, 0.001, f64::INFINITY, rng).is_some() {
+            return Color::new(0, 0, 0);
+        }
+        let bsdf_pdf = hit.material.pdf(ray, hit, sample.direction);
+        let weight = self.settings.heuristic.weight(sample.pdf, bsdf_pdf);
+        weight * f * sample.radiance / sample.pdf
+    }
+}
+
+/// Ray from `hit` towards `direction`, at the time of `ray`
+fn shadow_ray(ray: Ray, hit: &HitRecord, direction: Vec3) -> Ray {
+    Ray {
+        origin: hit.p,
+        direction,
+        time: ray.time,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, sync::Arc};
+
+    use super::*;
+    use crate::{
+        background::{Constant, EnvironmentMap},
+        hittable::{HittableList, Quad, Sphere},
+        material::{DiffuseLight, Lambertian, Metal},
+        scene::builtin,
+        vec3::{Point3, Vec3},
+    };
+
+    fn settings() -> RenderSettings {
+        RenderSettings {
+            width: 8,
+            height: 6,
+            samples_per_pixel: 2,
+            max_depth: 5,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_deterministic() {
+        let scene = builtin::three_spheres(settings());
+        let render = |seed| {
+            Renderer::new(settings())
+                .seed(seed)
+                .render(&scene.world, &scene.camera)
+        };
+
+        assert_eq!(render(1), render(1));
+        assert_ne!(render(1), render(2));
+    }
+
+    #[test]
+    fn test_progress() {
+        let scene = builtin::three_spheres(settings());
+        let calls = Cell::new(0);
+        Renderer::new(settings())
+            .on_progress(|progress| {
+                calls.set(calls.get() + 1);
+                assert_eq!(calls.get(), progress.rows_done);
+                assert_eq!(6, progress.rows_total);
+            })
+            .render(&scene.world, &scene.camera);
+        assert_eq!(6, calls.get());
+    }
+
+    #[test]
+    fn test_cancel() {
+        let scene = builtin::three_spheres(settings());
+        let cancel = AtomicBool::new(false);
+        let film = Renderer::new(settings())
+            .cancel_flag(&cancel)
+            .on_progress(|progress| {
+                if progress.rows_done == 2 {
+                    cancel.store(true, Ordering::Relaxed);
+                }
+            })
+            .render(&scene.world, &scene.camera);
+
+        assert_eq!(2, film.sample_count(0, 1));
+        assert_eq!(0, film.sample_count(0, 2));
+    }
+
+    #[test]
+    fn test_tiny_images() {
+        let scene = builtin::three_spheres(settings());
+        for (width, height) in [(1, 1), (1, 3), (0, 2)] {
+            let settings = RenderSettings {
+                width,
+                height,
+                ..settings()
+            };
+            let film = Renderer::new(settings).render(&scene.world, &scene.camera);
+            let image = film.image();
+            assert_eq!((width * height) as usize, image.pixels.len());
+            assert!(image
+                .pixels
+                .iter()
+                .all(|c| c.x().is_finite() && c.y().is_finite() && c.z().is_finite()));
+        }
+    }
+
+    #[test]
+    fn test_background() {
+        let scene = builtin::three_spheres(settings());
+        let world: HittableList = vec![];
+        let white = Constant::new(Color::new(1, 1, 1));
+        let film = Renderer::new(settings())
+            .background(&white)
+            .render(&world, &scene.camera);
+
+        let image = film.image();
+        assert!(image.pixels.iter().all(|&c| c == Color::new(1, 1, 1)));
+    }
+
+    #[test]
+    fn test_emission() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let ray = Ray {
+            origin: Point3::new(0, 0, 0),
+            direction: Vec3::new(0, 0, -1),
+            time: 0.0,
+        };
+
+        // Looking straight at a light
+        let light: HittableList = vec![Box::new(Sphere::new(
+            Point3::new(0, 0, -2),
+            1.0,
+            DiffuseLight::new(Color::new(4, 4, 4)),
+        ))];
+        let black = Constant::new(Color::new(0, 0, 0));
+        assert_eq!(
+            Color::new(4, 4, 4),
+            ray_color(ray, &light, &NO_LIGHTS, &black, &settings(), &mut rng)
+        );
+
+        // No light is gathered beyond the bounce limit
+        assert_eq!(
+            Color::new(0, 0, 0),
+            ray_color(
+                ray,
+                &light,
+                &NO_LIGHTS,
+                &black,
+                &RenderSettings {
+                    max_depth: 0,
+                    ..settings()
+                },
+                &mut rng
+            )
+        );
+    }
+
+    #[test]
+    fn test_direct_light() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        // A small light above a gray floor, looked at from straight above
+        let light = Arc::new(Sphere::new(
+            Point3::new(0, 2, 0),
+            0.5,
+            DiffuseLight::new(Color::new(4, 4, 4)),
+        ));
+        let lights: HittableList = vec![Box::new(Arc::clone(&light))];
+        let world: HittableList = vec![
+            Box::new(light),
+            Box::new(Quad::new(
+                Point3::new(-10, 0, -10),
+                Vec3::new(0, 0, 20),
+                Vec3::new(20, 0, 0),
+                Lambertian::new(Color::new(0.5, 0.5, 0.5)),
+            )),
+        ];
+        let black = Constant::new(Color::new(0, 0, 0));
+        let ray = Ray {
+            origin: Point3::new(0.1, 1, 0),
+            direction: Vec3::new(0, -1, 0),
+            time: 0.0,
+        };
+
+        // The light covers sin²θ = r²/d² of the floor's view, the rest is
+        // black, so only one bounce matters
+        let expected = 0.5 * 4.0 * 0.25 / (4.0 + 0.01);
+        let n = 10_000;
+        let mean = |lights: &dyn Hittable, rng: &mut SmallRng| {
+            (0..n)
+                .map(|_| ray_color(ray, &world, lights, &black, &settings(), rng))
+                .sum::<Color>()
+                .x()
+                / n as f64
+        };
+        let sampled = mean(&lights, &mut rng);
+        let bounced = mean(&NO_LIGHTS, &mut rng);
+        assert!((sampled - expected).abs() < 0.002, "{}", sampled);
+        assert!((bounced - expected).abs() < 0.02, "{}", bounced);
+    }
+
+    #[test]
+    fn test_environment_light() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let white = EnvironmentMap::new(8, 4, vec![Color::new(1, 1, 1); 32]);
+        let world: HittableList = vec![Box::new(Quad::new(
+            Point3::new(-10, 0, -10),
+            Vec3::new(0, 0, 20),
+            Vec3::new(20, 0, 0),
+            Lambertian::new(Color::new(0.5, 0.5, 0.5)),
+        ))];
+        let ray = Ray {
+            origin: Point3::new(0, 1, 0),
+            direction: Vec3::new(0, -1, 0),
+            time: 0.0,
+        };
+
+        // Lit evenly by the whole sky, which is only counted once
+        let n = 10_000;
+        let mean = (0..n)
+            .map(|_| ray_color(ray, &world, &NO_LIGHTS, &white, &settings(), &mut rng))
+            .sum::<Color>()
+            .x()
+            / n as f64;
+        assert!((mean - 0.5).abs() < 0.02, "{}", mean);
+    }
+
+    #[test]
+    fn test_heuristic() {
+        for heuristic in [Heuristic::Balance, Heuristic::Power] {
+            let (a, b) = (heuristic.weight(2.0, 0.5), heuristic.weight(0.5, 2.0));
+            assert!((a + b - 1.0).abs() < 1e-12);
+            assert_eq!(1.0, heuristic.weight(3.0, 0.0));
+        }
+        assert!(Heuristic::Power.weight(2.0, 0.5) > Heuristic::Balance.weight(2.0, 0.5));
+    }
+
+    #[test]
+    fn test_glossy() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        // A large light above a fuzzy metal floor
+        let light = Arc::new(Quad::new(
+            Point3::new(-2, 2, -2),
+            Vec3::new(4, 0, 0),
+            Vec3::new(0, 0, 4),
+            DiffuseLight::new(Color::new(2, 2, 2)),
+        ));
+        let lights: HittableList = vec![Box::new(Arc::clone(&light))];
+        let world: HittableList = vec![
+            Box::new(light),
+            Box::new(Quad::new(
+                Point3::new(-10, 0, -10),
+                Vec3::new(0, 0, 20),
+                Vec3::new(20, 0, 0),
+                Metal::new(Color::new(0.5, 0.5, 0.5), 0.8),
+            )),
+        ];
+        let black = Constant::new(Color::new(0, 0, 0));
+        let ray = Ray {
+            origin: Point3::new(0, 1, 1),
+            direction: Vec3::new(0, -1, -0.5),
+            time: 0.0,
+        };
+
+        // Every strategy agrees on average
+        let n = 40_000;
+        let mean = |lights: &dyn Hittable, heuristic, rng: &mut SmallRng| {
+            let settings = RenderSettings {
+                heuristic,
+                ..settings()
+            };
+            (0..n)
+                .map(|_| ray_color(ray, &world, lights, &black, &settings, rng))
+                .sum::<Color>()
+                .x()
+                / n as f64
+        };
+        let bounced = mean(&NO_LIGHTS, Heuristic::Power, &mut rng);
+        for heuristic in [Heuristic::Balance, Heuristic::Power] {
+            let combined = mean(&lights, heuristic, &mut rng);
+            assert!(
+                (combined - bounced).abs() < 0.03 * bounced,
+                "{:?}: {} != {}",
+                heuristic,
+                combined,
+                bounced
+            );
+        }
+    }
+
+    #[test]
+    fn test_roulette() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        // Light bouncing between a floor and a ceiling under a white sky
+        let world: HittableList = vec![
+            Box::new(Quad::new(
+                Point3::new(-2, 0, -2),
+                Vec3::new(0, 0, 4),
+                Vec3::new(4, 0, 0),
+                Lambertian::new(Color::new(0.8, 0.8, 0.8)),
+            )),
+            Box::new(Quad::new(
+                Point3::new(-2, 1, -2),
+                Vec3::new(4, 0, 0),
+                Vec3::new(0, 0, 4),
+                Lambertian::new(Color::new(0.8, 0.8, 0.8)),
+            )),
+        ];
+        let white = Constant::new(Color::new(1, 1, 1));
+        let ray = Ray {
+            origin: Point3::new(0, 0.5, 0),
+            direction: Vec3::new(0, -1, 0),
+            time: 0.0,
+        };
+
+        let n = 40_000;
+        let mean = |roulette_depth, rng: &mut SmallRng| {
+            let settings = RenderSettings {
+                max_depth: 50,
+                roulette_depth,
+                ..settings()
+            };
+            (0..n)
+                .map(|_| ray_color(ray, &world, &NO_LIGHTS, &white, &settings, rng))
+                .sum::<Color>()
+                .x()
+                / n as f64
+        };
+        let full = mean(50, &mut rng);
+        let roulette = mean(0, &mut rng);
+        assert!(
+            (full - roulette).abs() < 0.02 * full,
+            "{} != {}",
+            full,
+            roulette
+        );
+
+        // Paths between perfect mirrors carry all their light, so they only
+        // end at the bounce limit
+        let mirror: HittableList = vec![Box::new(Sphere::new(
+            Point3::new(0, 0, 0),
+            2.0,
+            Metal::new(Color::new(1, 1, 1), 0.0),
+        ))];
+        let settings = RenderSettings {
+            max_depth: u8::MAX,
+            roulette_depth: 0,
+            ..settings()
+        };
+        assert_eq!(
+            Color::new(0, 0, 0),
+            ray_color(ray, &mirror, &NO_LIGHTS, &white, &settings, &mut rng)
+        );
+    }
+
+    #[test]
+    fn test_environment_sampling() {
+        /// The same sky, without importance sampling
+        struct Unsampled(EnvironmentMap);
+
+        impl Background for Unsampled {
+            fn color(&self, ray: Ray) -> Color {
+                self.0.color(ray)
+            }
+        }
+
+        let mut rng = SmallRng::seed_from_u64(0);
+        // A small, bright sun high in an otherwise black sky
+        let mut pixels = vec![Color::new(0, 0, 0); 32 * 16];
+        pixels[2 * 32 + 16] = Color::new(1000, 1000, 1000);
+        let sky = EnvironmentMap::new(32, 16, pixels);
+        let world: HittableList = vec![Box::new(Quad::new(
+            Point3::new(-10, 0, -10),
+            Vec3::new(0, 0, 20),
+            Vec3::new(20, 0, 0),
+            Lambertian::new(Color::new(0.5, 0.5, 0.5)),
+        ))];
+        let ray = Ray {
+            origin: Point3::new(0, 1, 0),
+            direction: Vec3::new(0, -1, 0),
+            time: 0.0,
+        };
+
+        // Same mean, far fewer fireflies
+        let n = 100_000;
+        let stats = |background: &dyn Background, rng: &mut SmallRng| {
+            let values: Vec<f64> = (0..n)
+                .map(|_| ray_color(ray, &world, &NO_LIGHTS, background, &settings(), rng).x())
+                .collect();
+            let mean = values.iter().sum::<f64>() / n as f64;
+            let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n as f64;
+            (mean, variance)
+        };
+        let (sampled_mean, sampled_variance) = stats(&sky, &mut rng);
+        let (mean, variance) = stats(&Unsampled(sky), &mut rng);
+        assert!(
+            (sampled_mean - mean).abs() < 0.1 * mean,
+            "{} != {}",
+            sampled_mean,
+            mean
+        );
+        assert!(
+            sampled_variance < 0.1 * variance,
+            "{} vs {}",
+            sampled_variance,
+            variance
+        );
+    }
+}
@@ -0,0 +1,1625 @@
+//! Scene description files
+//!
+//! A scene is written in TOML and describes the camera, render settings,
+//! named textures and materials, and the objects using them:
+//!
+//! ```toml
+//! [camera]
+//! lookfrom = [13, 2, 3]
+//! lookat = [0, 0, 0]
+//! vfov = 20.0
+//! aperture = 0.1
+//! focus_dist = 10.0
+//!
+//! [render]
+//! width = 400
+//! height = 225
+//!
+//! [background]
+//! type = "gradient"
+//! bottom = [1, 1, 1]
+//! top = [0.5, 0.7, 1.0]
+//!
+//! [materials.ground]
+//! type = "lambertian"
+//! albedo = [0.5, 0.5, 0.5]
+//!
+//! [[objects]]
+//! type = "sphere"
+//! center = [0, -1000, 0]
+//! radius = 1000
+//! material = "ground"
+//! ```
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Display,
+    fs, io,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use serde::Deserialize;
+
+use crate::{
+    background::{self, Background, Constant, EnvironmentMap, Gradient},
+    camera::Camera,
+    density::{Density, Grid, NoiseDensity},
+    hittable::{
+        Aabb, Capsule, Cone, ConstantMedium, Cuboid, Cylinder, Disk, HeterogeneousMedium, Hittable,
+        HittableList, Mesh, MovingSphere, Plane, Quad, Sphere, Torus, Transformed, Triangle,
+    },
+    material::{
+        Dielectric, DiffuseLight, HenyeyGreenstein, Isotropic, Lambertian, Material, Metal,
+    },
+    obj,
+    renderer::RenderSettings,
+    texture::{self, Checker, Filter, ImageTexture, Noise, Pattern, Texture, Wrap},
+    transform::Transform,
+    vec3::{Color, Point3, Vec3},
+};
+
+pub mod builtin;
+
+#[derive(Debug)]
+pub enum Error {
+    Io {
+        path: PathBuf,
+        source: io::Error,
+    },
+    /// The file is not valid TOML, or does not match the expected structure
+    Syntax(toml::de::Error),
+    UnknownMaterial {
+        name: String,
+        object: usize,
+    },
+    Invalid(String),
+    Obj(obj::Error),
+    Background(background::Error),
+    Texture(texture::Error),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Io { path, source } => write!(f, "{}: {}", path.display(), source),
+            Error::Syntax(err) => write!(f, "{}", err),
+            Error::UnknownMaterial { name, object } => {
+                write!(f, "objects[{}]: unknown material `{}`", object, name)
+            }
+            Error::Invalid(message) => write!(f, "{}", message),
+            Error::Obj(err) => write!(f, "{}", err),
+            Error::Background(err) => write!(f, "{}", err),
+            Error::Texture(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io { source, .. } => Some(source),
+            Error::Syntax(err) => Some(err),
+            Error::Obj(err) => Some(err),
+            Error::Background(err) => Some(err),
+            Error::Texture(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+pub struct Scene {
+    pub world: HittableList,
+    /// Spheres, quads, disks and triangles of the world with a
+    /// `diffuse_light` material, to be sampled directly
+    pub lights: HittableList,
+    pub camera: Camera,
+    pub settings: RenderSettings,
+    pub background: Box<dyn Background + Sync>,
+}
+
+impl Scene {
+    /// Load a scene file. Paths in the scene are relative to the file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let source = fs::read_to_string(path).map_err(|source| Error::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        Self::parse(&source, path.parent().unwrap_or(Path::new("")))
+    }
+
+    /// Parse a scene. Paths in the scene are resolved relative to `base_dir`.
+    pub fn parse(source: &str, base_dir: &Path) -> Result<Self, Error> {
+        let file: SceneFile = toml::from_str(source).map_err(Error::Syntax)?;
+        file.build(base_dir)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SceneFile {
+    camera: CameraDesc,
+    #[serde(default)]
+    render: RenderSettings,
+    /// Defaults to `Gradient::sky()`
+    background: Option<BackgroundDesc>,
+    #[serde(default)]
+    textures: BTreeMap<String, TextureDesc>,
+    #[serde(default)]
+    materials: BTreeMap<String, MaterialDesc>,
+    #[serde(default)]
+    objects: Vec<ObjectDesc>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CameraDesc {
+    lookfrom: [f64; 3],
+    lookat: [f64; 3],
+    #[serde(default = "default_vup")]
+    vup: [f64; 3],
+    /// Vertical field of view in degrees
+    vfov: f64,
+    #[serde(default)]
+    aperture: f64,
+    /// Defaults to the distance between `lookfrom` and `lookat`
+    focus_dist: Option<f64>,
+    /// Shutter open and close times
+    #[serde(default)]
+    shutter: [f64; 2],
+}
+
+fn default_vup() -> [f64; 3] {
+    [0.0, 1.0, 0.0]
+}
+
+fn default_time1() -> f64 {
+    1.0
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase", deny_unknown_fields)]
+enum BackgroundDesc {
+    Constant {
+        color: [f64; 3],
+    },
+    Gradient {
+        bottom: [f64; 3],
+        top: [f64; 3],
+    },
+    /// An equirectangular panorama, usually HDR or EXR
+    Environment {
+        path: PathBuf,
+        /// Around the y axis, in degrees
+        #[serde(default)]
+        rotation: f64,
+        #[serde(default = "default_intensity")]
+        intensity: f64,
+    },
+}
+
+fn default_intensity() -> f64 {
+    1.0
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+enum TextureDesc {
+    /// A 3D checker pattern
+    Checker {
+        even: [f64; 3],
+        odd: [f64; 3],
+        #[serde(default = "default_scale")]
+        scale: f64,
+    },
+    /// A PNG, JPEG or HDR image, mapped using the surface's UV coordinates
+    Image {
+        path: PathBuf,
+        #[serde(default)]
+        filter: Filter,
+        #[serde(default)]
+        wrap: Wrap,
+    },
+    /// A procedural pattern, blending from `low` to `high`
+    Noise {
+        pattern: Pattern,
+        #[serde(default)]
+        seed: u64,
+        #[serde(default = "default_scale")]
+        scale: f64,
+        #[serde(default)]
+        low: [f64; 3],
+        #[serde(default = "default_high")]
+        high: [f64; 3],
+    },
+}
+
+fn default_scale() -> f64 {
+    1.0
+}
+
+fn default_high() -> [f64; 3] {
+    [1.0, 1.0, 1.0]
+}
+
+/// The albedo of a material is either a color or the name of a texture
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+enum MaterialDesc {
+    Lambertian {
+        albedo: Option<[f64; 3]>,
+        texture: Option<String>,
+    },
+    Metal {
+        albedo: Option<[f64; 3]>,
+        texture: Option<String>,
+        #[serde(default)]
+        fuzz: f64,
+    },
+    Dielectric {
+        /// Index of refraction
+        ir: f64,
+    },
+    DiffuseLight {
+        emit: [f64; 3],
+    },
+    /// Phase function of a medium, scattering in all directions
+    Isotropic {
+        albedo: Option<[f64; 3]>,
+        texture: Option<String>,
+    },
+    /// Phase function of a medium, scattering mostly forwards when `g` is
+    /// positive and backwards when it is negative
+    HenyeyGreenstein {
+        albedo: Option<[f64; 3]>,
+        texture: Option<String>,
+        #[serde(default)]
+        g: f64,
+    },
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+enum ObjectDesc {
+    Sphere {
+        center: [f64; 3],
+        radius: f64,
+        material: String,
+    },
+    /// A sphere moving from `center0` at `time0` to `center1` at `time1`
+    MovingSphere {
+        center0: [f64; 3],
+        center1: [f64; 3],
+        #[serde(default)]
+        time0: f64,
+        #[serde(default = "default_time1")]
+        time1: f64,
+        radius: f64,
+        material: String,
+    },
+    Triangle {
+        vertices: [[f64; 3]; 3],
+        normals: Option<[[f64; 3]; 3]>,
+        uvs: Option<[[f64; 2]; 3]>,
+        material: String,
+    },
+    /// Parallelogram with corners `q`, `q + u`, `q + v` and `q + u + v`
+    Quad {
+        q: [f64; 3],
+        u: [f64; 3],
+        v: [f64; 3],
+        material: String,
+    },
+    Disk {
+        center: [f64; 3],
+        normal: [f64; 3],
+        radius: f64,
+        material: String,
+    },
+    /// Infinite plane through `point`
+    Plane {
+        point: [f64; 3],
+        normal: [f64; 3],
+        material: String,
+    },
+    /// Axis-aligned box between two opposite corners
+    Box {
+        min: [f64; 3],
+        max: [f64; 3],
+        material: String,
+    },
+    /// Capped cylinder around the segment from `base` to `top`
+    Cylinder {
+        base: [f64; 3],
+        top: [f64; 3],
+        radius: f64,
+        material: String,
+    },
+    Cone {
+        base: [f64; 3],
+        apex: [f64; 3],
+        radius: f64,
+        material: String,
+    },
+    /// Points within `radius` of the segment from `a` to `b`
+    Capsule {
+        a: [f64; 3],
+        b: [f64; 3],
+        radius: f64,
+        material: String,
+    },
+    Torus {
+        center: [f64; 3],
+        #[serde(default = "default_axis")]
+        axis: [f64; 3],
+        major_radius: f64,
+        minor_radius: f64,
+        material: String,
+    },
+    /// Smoke or fog filling `boundary`, scattering light following the
+    /// boundary's material, e.g. an isotropic one
+    ConstantMedium {
+        boundary: Box<ObjectDesc>,
+        density: f64,
+    },
+    /// Medium whose density varies inside `boundary`, like clouds. The
+    /// coefficients are per unit of density.
+    HeterogeneousMedium {
+        boundary: Box<ObjectDesc>,
+        density: DensityDesc,
+        #[serde(default)]
+        absorption: f64,
+        #[serde(default = "default_scattering")]
+        scattering: f64,
+    },
+    /// An OBJ file. If `material` is given, it replaces the MTL materials.
+    /// With `instances`, the mesh is loaded once and placed once per entry.
+    Mesh {
+        path: PathBuf,
+        material: Option<String>,
+        #[serde(default)]
+        instances: Vec<TransformDesc>,
+    },
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+enum DensityDesc {
+    Constant {
+        value: f64,
+    },
+    /// Values on a grid of points spanning the box from `min` to `max`,
+    /// which defaults to the bounding box of the medium. The values are
+    /// listed along x first, then y, then z.
+    Grid {
+        resolution: [usize; 3],
+        values: Vec<f64>,
+        min: Option<[f64; 3]>,
+        max: Option<[f64; 3]>,
+    },
+    /// Perlin turbulence, from zero up to `max`
+    Noise {
+        #[serde(default)]
+        seed: u64,
+        #[serde(default = "default_scale")]
+        scale: f64,
+        max: f64,
+    },
+}
+
+fn default_scattering() -> f64 {
+    1.0
+}
+
+/// Scale, then rotate around the x, y and z axes, then translate
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TransformDesc {
+    #[serde(default = "default_scale3")]
+    scale: [f64; 3],
+    /// Degrees around the x, y and z axes
+    #[serde(default)]
+    rotate: [f64; 3],
+    #[serde(default)]
+    translate: [f64; 3],
+}
+
+fn default_axis() -> [f64; 3] {
+    [0.0, 1.0, 0.0]
+}
+
+fn default_scale3() -> [f64; 3] {
+    [1.0, 1.0, 1.0]
+}
+
+type SharedMaterial = Arc<dyn Material + Send + Sync>;
+type SharedTexture = Arc<dyn Texture + Send + Sync>;
+type SharedDensity = Arc<dyn Density + Send + Sync>;
+
+fn vec3([x, y, z]: [f64; 3]) -> Vec3 {
+    Vec3(x, y, z)
+}
+
+fn check(condition: bool, message: impl FnOnce() -> String) -> Result<(), Error> {
+    if condition {
+        Ok(())
+    } else {
+        Err(Error::Invalid(message()))
+    }
+}
+
+/// Checks the ends and radius of a shape around a segment
+fn check_segment(index: usize, a: [f64; 3], b: [f64; 3], radius: f64) -> Result<(), Error> {
+    check(radius > 0.0, || {
+        format!("objects[{}]: radius must be positive", index)
+    })?;
+    check(a != b, || {
+        format!("objects[{}]: the ends must differ", index)
+    })
+}
+
+impl SceneFile {
+    fn build(self, base_dir: &Path) -> Result<Scene, Error> {
+        let settings = self.render;
+        check(settings.width > 0 && settings.height > 0, || {
+            "render: width and height must be positive".to_string()
+        })?;
+        check(settings.samples_per_pixel > 0, || {
+            "render: samples_per_pixel must be positive".to_string()
+        })?;
+        check(settings.max_depth > 0, || {
+            "render: max_depth must be positive".to_string()
+        })?;
+
+        let camera = self.camera.build(settings.aspect_ratio())?;
+        let background = match self.background {
+            Some(desc) => desc.build(base_dir)?,
+            None => Box::new(Gradient::sky()),
+        };
+
+        let mut textures: BTreeMap<String, SharedTexture> = BTreeMap::new();
+        for (name, desc) in self.textures {
+            let texture = desc.build(&name, base_dir)?;
+            textures.insert(name, texture);
+        }
+
+        let mut materials: BTreeMap<String, SharedMaterial> = BTreeMap::new();
+        let mut emitters = BTreeSet::new();
+        let mut phase_functions = BTreeSet::new();
+        for (name, desc) in self.materials {
+            match desc {
+                MaterialDesc::DiffuseLight { .. } => {
+                    emitters.insert(name.clone());
+                }
+                MaterialDesc::Isotropic { .. } | MaterialDesc::HenyeyGreenstein { .. } => {
+                    phase_functions.insert(name.clone());
+                }
+                _ => {}
+            }
+            let material = desc.build(&name, &textures)?;
+            materials.insert(name, material);
+        }
+
+        let mut world: HittableList = vec![];
+        let mut lights: HittableList = vec![];
+        for (index, object) in self.objects.into_iter().enumerate() {
+            let material = |name: &str| {
+                materials
+                    .get(name)
+                    .cloned()
+                    .ok_or_else(|| Error::UnknownMaterial {
+                        name: name.to_string(),
+                        object: index,
+                    })
+            };
+            // Media scatter light with the material of their boundary
+            if let ObjectDesc::ConstantMedium { boundary, .. }
+            | ObjectDesc::HeterogeneousMedium { boundary, .. } = &object
+            {
+                if let Some(name) = boundary.material_name() {
+                    check(
+                        !materials.contains_key(name) || phase_functions.contains(name),
+                        || {
+                            format!(
+                                "objects[{}]: the medium's material `{}` must be a phase function",
+                                index, name
+                            )
+                        },
+                    )?;
+                }
+            }
+            let is_light = object
+                .material_name()
+                .is_some_and(|name| emitters.contains(name));
+            if is_light && object.can_be_sampled() {
+                // Shared between the world and the lights
+                let mut parts: HittableList = vec![];
+                object.build(index, base_dir, &material, &mut parts)?;
+                let light = Arc::new(parts);
+                world.push(Box::new(Arc::clone(&light)));
+                lights.push(Box::new(light));
+            } else {
+                object.build(index, base_dir, &material, &mut world)?;
+            }
+        }
+
+        Ok(Scene {
+            world,
+            lights,
+            camera,
+            settings,
+            background,
+        })
+    }
+}
+
+impl ObjectDesc {
+    /// Whether the object can be sampled directly, as a light
+    fn can_be_sampled(&self) -> bool {
+        matches!(
+            self,
+            ObjectDesc::Sphere { .. }
+                | ObjectDesc::Quad { .. }
+                | ObjectDesc::Disk { .. }
+                | ObjectDesc::Triangle { .. }
+        )
+    }
+
+    /// Name of the material of the whole object, if it has a single one
+    fn material_name(&self) -> Option<&str> {
+        match self {
+            ObjectDesc::Sphere { material, .. }
+            | ObjectDesc::MovingSphere { material, .. }
+            | ObjectDesc::Triangle { material, .. }
+            | ObjectDesc::Quad { material, .. }
+            | ObjectDesc::Disk { material, .. }
+            | ObjectDesc::Plane { material, .. }
+            | ObjectDesc::Box { material, .. }
+            | ObjectDesc::Cylinder { material, .. }
+            | ObjectDesc::Cone { material, .. }
+            | ObjectDesc::Capsule { material, .. }
+            | ObjectDesc::Torus { material, .. } => Some(material),
+            ObjectDesc::ConstantMedium { boundary, .. }
+            | ObjectDesc::HeterogeneousMedium { boundary, .. } => boundary.material_name(),
+            ObjectDesc::Mesh { material, .. } => material.as_deref(),
+        }
+    }
+
+    /// Adds the object to `world`, looking up materials by name with
+    /// `material`
+    fn build(
+        self,
+        index: usize,
+        base_dir: &Path,
+        material: &dyn Fn(&str) -> Result<SharedMaterial, Error>,
+        world: &mut HittableList,
+    ) -> Result<(), Error> {
+        match self {
+            ObjectDesc::Sphere {
+                center,
+                radius,
+                material: name,
+            } => {
+                check(radius > 0.0, || {
+                    format!("objects[{}]: radius must be positive", index)
+                })?;
+                world.push(Box::new(Sphere::new(
+                    vec3(center),
+                    radius,
+                    material(&name)?,
+                )));
+            }
+            ObjectDesc::MovingSphere {
+                center0,
+                center1,
+                time0,
+                time1,
+                radius,
+                material: name,
+            } => {
+                check(radius > 0.0, || {
+                    format!("objects[{}]: radius must be positive", index)
+                })?;
+                world.push(Box::new(MovingSphere::new(
+                    vec3(center0),
+                    vec3(center1),
+                    time0,
+                    time1,
+                    radius,
+                    material(&name)?,
+                )));
+            }
+            ObjectDesc::Triangle {
+                vertices: [a, b, c],
+                normals,
+                uvs,
+                material: name,
+            } => {
+                let mut triangle = Triangle::new(vec3(a), vec3(b), vec3(c), material(&name)?);
+                if let Some(normals) = normals {
+                    triangle = triangle.with_normals(normals.map(vec3));
+                }
+                if let Some(uvs) = uvs {
+                    triangle = triangle.with_uvs(uvs.map(|[u, v]| (u, v)));
+                }
+                world.push(Box::new(triangle));
+            }
+            ObjectDesc::Quad {
+                q,
+                u,
+                v,
+                material: name,
+            } => {
+                check(!vec3(u).cross(vec3(v)).near_zero(), || {
+                    format!("objects[{}]: u and v must not be parallel", index)
+                })?;
+                world.push(Box::new(Quad::new(
+                    vec3(q),
+                    vec3(u),
+                    vec3(v),
+                    material(&name)?,
+                )));
+            }
+            ObjectDesc::Disk {
+                center,
+                normal,
+                radius,
+                material: name,
+            } => {
+                check(radius > 0.0, || {
+                    format!("objects[{}]: radius must be positive", index)
+                })?;
+                check(!vec3(normal).near_zero(), || {
+                    format!("objects[{}]: normal must not be zero", index)
+                })?;
+                world.push(Box::new(Disk::new(
+                    vec3(center),
+                    vec3(normal),
+                    radius,
+                    material(&name)?,
+                )));
+            }
+            ObjectDesc::Plane {
+                point,
+                normal,
+                material: name,
+            } => {
+                check(!vec3(normal).near_zero(), || {
+                    format!("objects[{}]: normal must not be zero", index)
+                })?;
+                world.push(Box::new(Plane::new(
+                    vec3(point),
+                    vec3(normal),
+                    material(&name)?,
+                )));
+            }
+            ObjectDesc::Box {
+                min,
+                max,
+                material: name,
+            } => {
+                check((0..3).all(|i| min[i] < max[i]), || {
+                    format!("objects[{}]: min must be below max on every axis", index)
+                })?;
+                world.push(Box::new(Cuboid::new(
+                    vec3(min),
+                    vec3(max),
+                    material(&name)?,
+                )));
+            }
+            ObjectDesc::Cylinder {
+                base,
+                top,
+                radius,
+                material: name,
+            } => {
+                check_segment(index, base, top, radius)?;
+                world.push(Box::new(Cylinder::new(
+                    vec3(base),
+                    vec3(top),
+                    radius,
+                    material(&name)?,
+                )));
+            }
+            ObjectDesc::Cone {
+                base,
+                apex,
+                radius,
+                material: name,
+            } => {
+                check_segment(index, base, apex, radius)?;
+                world.push(Box::new(Cone::new(
+                    vec3(base),
+                    vec3(apex),
+                    radius,
+                    material(&name)?,
+                )));
+            }
+            ObjectDesc::Capsule {
+                a,
+                b,
+                radius,
+                material: name,
+            } => {
+                check_segment(index, a, b, radius)?;
+                world.push(Box::new(Capsule::new(
+                    vec3(a),
+                    vec3(b),
+                    radius,
+                    material(&name)?,
+                )));
+            }
+            ObjectDesc::Torus {
+                center,
+                axis,
+                major_radius,
+                minor_radius,
+                material: name,
+            } => {
+                check(!vec3(axis).near_zero(), || {
+                    format!("objects[{}]: axis must not be zero", index)
+                })?;
+                check(0.0 < minor_radius && minor_radius <= major_radius, || {
+                    format!(
+                        "objects[{}]: minor_radius must be positive and at most major_radius",
+                        index
+                    )
+                })?;
+                world.push(Box::new(Torus::new(
+                    vec3(center),
+                    vec3(axis),
+                    major_radius,
+                    minor_radius,
+                    material(&name)?,
+                )));
+            }
+            ObjectDesc::ConstantMedium { boundary, density } => {
+                check(density > 0.0, || {
+                    format!("objects[{}]: density must be positive", index)
+                })?;
+                let (parts, phase_function) = boundary.build_boundary(index, base_dir, material)?;
+                world.push(Box::new(ConstantMedium::new(
+                    parts,
+                    density,
+                    phase_function,
+                )));
+            }
+            ObjectDesc::HeterogeneousMedium {
+                boundary,
+                density,
+                absorption,
+                scattering,
+            } => {
+                check(absorption >= 0.0 && scattering >= 0.0, || {
+                    format!(
+                        "objects[{}]: absorption and scattering must not be negative",
+                        index
+                    )
+                })?;
+                let (parts, phase_function) = boundary.build_boundary(index, base_dir, material)?;
+                let density = density.build(index, parts.bounding_box())?;
+                world.push(Box::new(
+                    HeterogeneousMedium::new(parts, density, phase_function)
+                        .with_coefficients(absorption, scattering),
+                ));
+            }
+            ObjectDesc::Mesh {
+                path,
+                material: name,
+                instances,
+            } => {
+                let mut data = obj::load_data(base_dir.join(path)).map_err(Error::Obj)?;
+                if let Some(name) = name {
+                    data.materials = vec![Box::new(material(&name)?)];
+                    data.faces.iter_mut().for_each(|face| face.material = 0);
+                }
+                let mesh = Mesh::new(data);
+                if instances.is_empty() {
+                    world.push(Box::new(mesh));
+                } else {
+                    let mesh = Arc::new(mesh);
+                    for (i, instance) in instances.into_iter().enumerate() {
+                        let transform = instance.build(index, i)?;
+                        world.push(Box::new(Transformed::new(Arc::clone(&mesh), transform)));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Builds the boundary of a medium, along with its material, which is
+    /// the medium's phase function
+    fn build_boundary(
+        self,
+        index: usize,
+        base_dir: &Path,
+        material: &dyn Fn(&str) -> Result<SharedMaterial, Error>,
+    ) -> Result<(HittableList, SharedMaterial), Error> {
+        check(
+            !matches!(
+                self,
+                ObjectDesc::ConstantMedium { .. } | ObjectDesc::HeterogeneousMedium { .. }
+            ),
+            || format!("objects[{}]: the boundary must be a surface", index),
+        )?;
+        let name = self.material_name().ok_or_else(|| {
+            Error::Invalid(format!("objects[{}]: the boundary needs a material", index))
+        })?;
+        let phase_function = material(name)?;
+        let mut parts: HittableList = vec![];
+        self.build(index, base_dir, material, &mut parts)?;
+        Ok((parts, phase_function))
+    }
+}
+
+impl DensityDesc {
+    /// `bounds` are those of the medium's boundary
+    fn build(self, index: usize, bounds: Option<Aabb>) -> Result<SharedDensity, Error> {
+        Ok(match self {
+            DensityDesc::Constant { value } => {
+                check(value >= 0.0, || {
+                    format!("objects[{}]: density must not be negative", index)
+                })?;
+                Arc::new(value)
+            }
+            DensityDesc::Grid {
+                resolution,
+                values,
+                min,
+                max,
+            } => {
+                check(resolution.iter().all(|&n| n >= 2), || {
+                    format!(
+                        "objects[{}]: the grid needs at least 2 points along each axis",
+                        index
+                    )
+                })?;
+                check(resolution.iter().product::<usize>() == values.len(), || {
+                    format!("objects[{}]: the grid needs one value per point", index)
+                })?;
+                check(values.iter().all(|&v| v >= 0.0), || {
+                    format!("objects[{}]: densities must not be negative", index)
+                })?;
+                let bounds = match (min, max, bounds) {
+                    (Some(min), Some(max), _) => Aabb::new(vec3(min), vec3(max)),
+                    (None, None, Some(bounds)) => bounds,
+                    _ => {
+                        return Err(Error::Invalid(format!(
+                            "objects[{}]: the grid needs both min and max",
+                            index
+                        )))
+                    }
+                };
+                let (min, max) = (bounds.minimum, bounds.maximum);
+                check(
+                    min.x() < max.x() && min.y() < max.y() && min.z() < max.z(),
+                    || format!("objects[{}]: min must be below max on every axis", index),
+                )?;
+                Arc::new(Grid::new(bounds, resolution, values))
+            }
+            DensityDesc::Noise { seed, scale, max } => {
+                check(max >= 0.0, || {
+                    format!("objects[{}]: max must not be negative", index)
+                })?;
+                Arc::new(NoiseDensity::new(seed, max).with_scale(scale))
+            }
+        })
+    }
+}
+
+impl CameraDesc {
+    fn build(self, aspect_ratio: f64) -> Result<Camera, Error> {
+        let lookfrom: Point3 = vec3(self.lookfrom);
+        let lookat: Point3 = vec3(self.lookat);
+        let vup = vec3(self.vup);
+
+        check(lookfrom != lookat, || {
+            "camera: lookfrom and lookat must differ".to_string()
+        })?;
+        check(!vup.cross(lookfrom - lookat).near_zero(), || {
+            "camera: vup must not be parallel to the view direction".to_string()
+        })?;
+        check(self.vfov > 0.0 && self.vfov < 180.0, || {
+            "camera: vfov must be between 0 and 180 degrees".to_string()
+        })?;
+        check(self.aperture >= 0.0, || {
+            "camera: aperture must not be negative".to_string()
+        })?;
+        let focus_dist = self
+            .focus_dist
+            .unwrap_or_else(|| (lookfrom - lookat).length());
+        check(focus_dist > 0.0, || {
+            "camera: focus_dist must be positive".to_string()
+        })?;
+        let [open, close] = self.shutter;
+        check(open <= close, || {
+            "camera: shutter must not close before it opens".to_string()
+        })?;
+
+        Ok(Camera::new(
+            lookfrom,
+            lookat,
+            vup,
+            self.vfov,
+            aspect_ratio,
+            self.aperture,
+            focus_dist,
+        )
+        .with_shutter(open, close))
+    }
+}
+
+impl TransformDesc {
+    fn build(self, object: usize, instance: usize) -> Result<Transform, Error> {
+        check(self.scale.iter().all(|&s| s != 0.0), || {
+            format!(
+                "objects[{}].instances[{}]: scale must not be zero",
+                object, instance
+            )
+        })?;
+        let [x, y, z] = self.rotate;
+        Ok(Transform::translate(vec3(self.translate))
+            * Transform::rotate_z(z)
+            * Transform::rotate_y(y)
+            * Transform::rotate_x(x)
+            * Transform::scale(vec3(self.scale)))
+    }
+}
+
+impl BackgroundDesc {
+    fn build(self, base_dir: &Path) -> Result<Box<dyn Background + Sync>, Error> {
+        let check_color = |color: [f64; 3]| {
+            check(color.iter().all(|&c| c >= 0.0), || {
+                "background: colors must not be negative".to_string()
+            })
+        };
+
+        Ok(match self {
+            BackgroundDesc::Constant { color } => {
+                check_color(color)?;
+                Box::new(Constant::new(vec3(color)))
+            }
+            BackgroundDesc::Gradient { bottom, top } => {
+                check_color(bottom)?;
+                check_color(top)?;
+                Box::new(Gradient::new(vec3(bottom), vec3(top)))
+            }
+            BackgroundDesc::Environment {
+                path,
+                rotation,
+                intensity,
+            } => {
+                check(intensity >= 0.0, || {
+                    "background: intensity must not be negative".to_string()
+                })?;
+                let map = EnvironmentMap::load(base_dir.join(path)).map_err(Error::Background)?;
+                Box::new(map.with_rotation(rotation).with_intensity(intensity))
+            }
+        })
+    }
+}
+
+impl TextureDesc {
+    fn build(self, name: &str, base_dir: &Path) -> Result<SharedTexture, Error> {
+        Ok(match self {
+            TextureDesc::Checker { even, odd, scale } => {
+                check(scale > 0.0, || {
+                    format!("textures.{}: scale must be positive", name)
+                })?;
+                Arc::new(Checker::new(vec3(even), vec3(odd), scale))
+            }
+            TextureDesc::Image { path, filter, wrap } => {
+                let texture = ImageTexture::load(base_dir.join(path)).map_err(Error::Texture)?;
+                Arc::new(texture.with_filter(filter).with_wrap(wrap))
+            }
+            TextureDesc::Noise {
+                pattern,
+                seed,
+                scale,
+                low,
+                high,
+            } => {
+                check(scale > 0.0, || {
+                    format!("textures.{}: scale must be positive", name)
+                })?;
+                Arc::new(
+                    Noise::new(pattern, seed)
+                        .with_scale(scale)
+                        .with_colors(vec3(low), vec3(high)),
+                )
+            }
+        })
+    }
+}
+
+/// Albedo given by either a color or a texture
+enum Albedo {
+    Color(Color),
+    Texture(SharedTexture),
+}
+
+impl MaterialDesc {
+    fn build(
+        self,
+        name: &str,
+        textures: &BTreeMap<String, SharedTexture>,
+    ) -> Result<SharedMaterial, Error> {
+        let albedo = |albedo: Option<[f64; 3]>, texture: Option<String>| match (albedo, texture) {
+            (Some(albedo), None) => {
+                check(albedo.iter().all(|&c| c >= 0.0), || {
+                    format!("materials.{}: albedo must not be negative", name)
+                })?;
+                Ok(Albedo::Color(vec3(albedo)))
+            }
+            (None, Some(texture)) => textures
+                .get(&texture)
+                .cloned()
+                .map(Albedo::Texture)
+                .ok_or_else(|| {
+                    Error::Invalid(format!("materials.{}: unknown texture `{}`", name, texture))
+                }),
+            _ => Err(Error::Invalid(format!(
+                "materials.{}: needs either an albedo or a texture",
+                name
+            ))),
+        };
+
+        Ok(match self {
+            MaterialDesc::Lambertian { albedo: a, texture } => match albedo(a, texture)? {
+                Albedo::Color(color) => Arc::new(Lambertian::new(color)),
+                Albedo::Texture(texture) => Arc::new(Lambertian::new(texture)),
+            },
+            MaterialDesc::Metal {
+                albedo: a,
+                texture,
+                fuzz,
+            } => {
+                check((0.0..=1.0).contains(&fuzz), || {
+                    format!("materials.{}: fuzz must be between 0 and 1", name)
+                })?;
+                match albedo(a, texture)? {
+                    Albedo::Color(color) => Arc::new(Metal::new(color, fuzz)),
+                    Albedo::Texture(texture) => Arc::new(Metal::new(texture, fuzz)),
+                }
+            }
+            MaterialDesc::Dielectric { ir } => {
+                check(ir > 0.0, || {
+                    format!("materials.{}: ir must be positive", name)
+                })?;
+                Arc::new(Dielectric::new(ir))
+            }
+            MaterialDesc::DiffuseLight { emit } => {
+                check(emit.iter().all(|&c| c >= 0.0), || {
+                    format!("materials.{}: emit must not be negative", name)
+                })?;
+                Arc::new(DiffuseLight::new(vec3(emit)))
+            }
+            MaterialDesc::Isotropic { albedo: a, texture } => match albedo(a, texture)? {
+                Albedo::Color(color) => Arc::new(Isotropic::new(color)),
+                Albedo::Texture(texture) => Arc::new(Isotropic::new(texture)),
+            },
+            MaterialDesc::HenyeyGreenstein {
+                albedo: a,
+                texture,
+                g,
+            } => {
+                check(-1.0 < g && g < 1.0, || {
+                    format!("materials.{}: g must be between -1 and 1", name)
+                })?;
+                match albedo(a, texture)? {
+                    Albedo::Color(color) => Arc::new(HenyeyGreenstein::new(color, g)),
+                    Albedo::Texture(texture) => Arc::new(HenyeyGreenstein::new(texture, g)),
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{film::Image, output, ray::Ray, renderer::Heuristic};
+
+    const CAMERA: &str = "[camera]\nlookfrom = [0, 0, 1]\nlookat = [0, 0, 0]\nvfov = 90\n";
+
+    fn parse(source: &str) -> Result<Scene, Error> {
+        Scene::parse(source, Path::new("."))
+    }
+
+    fn parse_err(source: &str) -> String {
+        match parse(source) {
+            Ok(_) => panic!("expected an error"),
+            Err(err) => err.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        let scene = parse(&format!(
+            "{}\n\
+             [render]\n\
+             width = 200\n\
+             height = 100\n\
+             samples_per_pixel = 10\n\
+             roulette_depth = 5\n\
+             heuristic = \"balance\"\n\
+             \n\
+             [materials.matte]\n\
+             type = \"lambertian\"\n\
+             albedo = [0.5, 0.5, 0.5]\n\
+             \n\
+             [textures.marble]\n\
+             type = \"noise\"\n\
+             pattern = \"marble\"\n\
+             scale = 4\n\
+             \n\
+             [textures.checks]\n\
+             type = \"checker\"\n\
+             even = [0, 0, 0]\n\
+             odd = [1, 1, 1]\n\
+             \n\
+             [materials.mirror]\n\
+             type = \"metal\"\n\
+             texture = \"checks\"\n\
+             \n\
+             [materials.glass]\n\
+             type = \"dielectric\"\n\
+             ir = 1.5\n\
+             \n\
+             [materials.lamp]\n\
+             type = \"diffuse_light\"\n\
+             emit = [4, 4, 4]\n\
+             \n\
+             [[objects]]\n\
+             type = \"sphere\"\n\
+             center = [0, 0, -1]\n\
+             radius = 0.5\n\
+             material = \"glass\"\n\
+             \n\
+             [[objects]]\n\
+             type = \"triangle\"\n\
+             vertices = [[0, 0, 0], [1, 0, 0], [0, 1, 0]]\n\
+             material = \"mirror\"\n\
+             \n\
+             [[objects]]\n\
+             type = \"moving_sphere\"\n\
+             center0 = [0, 0, -1]\n\
+             center1 = [0, 1, -1]\n\
+             radius = 0.5\n\
+             material = \"matte\"\n",
+            CAMERA
+        ))
+        .unwrap();
+
+        assert_eq!(3, scene.world.len());
+        assert_eq!(
+            RenderSettings {
+                width: 200,
+                height: 100,
+                samples_per_pixel: 10,
+                max_depth: 50,
+                roulette_depth: 5,
+                heuristic: Heuristic::Balance,
+            },
+            scene.settings
+        );
+    }
+
+    #[test]
+    fn test_lights() {
+        let scene = parse(&format!(
+            "{}\n\
+             [materials.lamp]\n\
+             type = \"diffuse_light\"\n\
+             emit = [4, 4, 4]\n\
+             \n\
+             [materials.matte]\n\
+             type = \"lambertian\"\n\
+             albedo = [0.5, 0.5, 0.5]\n\
+             \n\
+             [[objects]]\n\
+             type = \"quad\"\n\
+             q = [0, 2, 0]\n\
+             u = [1, 0, 0]\n\
+             v = [0, 0, 1]\n\
+             material = \"lamp\"\n\
+             \n\
+             [[objects]]\n\
+             type = \"sphere\"\n\
+             center = [0, 0, -1]\n\
+             radius = 0.5\n\
+             material = \"lamp\"\n\
+             \n\
+             [[objects]]\n\
+             type = \"sphere\"\n\
+             center = [0, 0, -3]\n\
+             radius = 0.5\n\
+             material = \"matte\"\n\
+             \n\
+             [[objects]]\n\
+             type = \"disk\"\n\
+             center = [0, 3, 0]\n\
+             normal = [0, -1, 0]\n\
+             radius = 1\n\
+             material = \"lamp\"\n\
+             \n\
+             [[objects]]\n\
+             type = \"triangle\"\n\
+             vertices = [[0, 4, 0], [1, 4, 0], [0, 4, 1]]\n\
+             material = \"lamp\"\n\
+             \n\
+             [[objects]]\n\
+             type = \"box\"\n\
+             min = [2, 0, 0]\n\
+             max = [3, 1, 1]\n\
+             material = \"lamp\"\n",
+            CAMERA
+        ))
+        .unwrap();
+
+        // Boxes can't be sampled yet
+        assert_eq!(6, scene.world.len());
+        assert_eq!(4, scene.lights.len());
+    }
+
+    #[test]
+    fn test_background() {
+        let ray = Ray {
+            origin: Point3::new(0, 0, 0),
+            direction: Vec3::new(0, 1, 0),
+            time: 0.0,
+        };
+        let scene = parse(CAMERA).unwrap();
+        assert_eq!(Color::new(0.5, 0.7, 1.0), scene.background.color(ray));
+
+        let scene = parse(&format!(
+            "{}\n[background]\ntype = \"constant\"\ncolor = [0, 0, 0]\n",
+            CAMERA
+        ))
+        .unwrap();
+        assert_eq!(Color::new(0, 0, 0), scene.background.color(ray));
+
+        let err = parse_err(&format!(
+            "{}\n[background]\ntype = \"gradient\"\nbottom = [1, 1, 1]\ntop = [0, -1, 0]\n",
+            CAMERA
+        ));
+        assert_eq!("background: colors must not be negative", err);
+    }
+
+    #[test]
+    fn test_environment() {
+        let dir = std::env::temp_dir();
+        let name = format!("raytracing-env-{}.hdr", std::process::id());
+        let image = Image {
+            width: 2,
+            height: 1,
+            pixels: vec![Color::new(1, 2, 3), Color::new(4, 5, 6)],
+        };
+        output::save(&image, &dir.join(&name)).unwrap();
+
+        let scene = Scene::parse(
+            &format!(
+                "{}\n[background]\ntype = \"environment\"\npath = \"{}\"\nintensity = 2\n",
+                CAMERA, name
+            ),
+            &dir,
+        );
+        fs::remove_file(dir.join(&name)).unwrap();
+
+        let ray = Ray {
+            origin: Point3::new(0, 0, 0),
+            direction: Vec3::new(1, 0, 0),
+            time: 0.0,
+        };
+        assert_eq!(Color::new(8, 10, 12), scene.unwrap().background.color(ray));
+
+        let err = parse_err(&format!(
+            "{}\n[background]\ntype = \"environment\"\npath = \"missing.hdr\"\n",
+            CAMERA
+        ));
+        assert!(err.starts_with("./missing.hdr: "), "{}", err);
+    }
+
+    #[test]
+    fn test_instances() {
+        let dir = std::env::temp_dir();
+        let name = format!("raytracing-instances-{}.obj", std::process::id());
+        fs::write(dir.join(&name), "v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\n").unwrap();
+
+        let source = format!(
+            "{}\n\
+             [[objects]]\n\
+             type = \"mesh\"\n\
+             path = \"{}\"\n\
+             instances = [\n\
+                 {{ translate = [5, 0, 0] }},\n\
+                 {{ scale = [2, 2, 2], rotate = [0, 90, 0] }},\n\
+             ]\n",
+            CAMERA, name
+        );
+        let scene = Scene::parse(&source, &dir);
+        let err = Scene::parse(&source.replace("[2, 2, 2]", "[2, 0, 2]"), &dir)
+            .err()
+            .unwrap();
+        fs::remove_file(dir.join(&name)).unwrap();
+
+        let scene = scene.unwrap();
+        assert_eq!(2, scene.world.len());
+        let bbox = scene.world[0].bounding_box().unwrap();
+        assert!((bbox.minimum - Point3::new(5, 0, 0)).length() < 1e-3);
+        let bbox = scene.world[1].bounding_box().unwrap();
+        assert!((bbox.maximum - Point3::new(0, 2, 0)).length() < 1e-3);
+        assert!((bbox.minimum - Point3::new(0, 0, -2)).length() < 1e-3);
+
+        assert_eq!(
+            "objects[0].instances[1]: scale must not be zero",
+            err.to_string()
+        );
+    }
+
+    #[test]
+    fn test_planar_objects() {
+        let objects = format!(
+            "{}\n\
+             [materials.a]\n\
+             type = \"lambertian\"\n\
+             albedo = [0.5, 0.5, 0.5]\n\
+             \n\
+             [[objects]]\n\
+             type = \"quad\"\n\
+             q = [0, 0, 0]\n\
+             u = [1, 0, 0]\n\
+             v = [0, 1, 0]\n\
+             material = \"a\"\n\
+             \n\
+             [[objects]]\n\
+             type = \"disk\"\n\
+             center = [0, 0, -1]\n\
+             normal = [0, 0, 1]\n\
+             radius = 2\n\
+             material = \"a\"\n\
+             \n\
+             [[objects]]\n\
+             type = \"plane\"\n\
+             point = [0, -1, 0]\n\
+             normal = [0, 1, 0]\n\
+             material = \"a\"\n\
+             \n\
+             [[objects]]\n\
+             type = \"box\"\n\
+             min = [-1, -1, -3]\n\
+             max = [1, 1, -2]\n\
+             material = \"a\"\n",
+            CAMERA
+        );
+        let scene = parse(&objects).unwrap();
+        assert_eq!(4, scene.world.len());
+        assert!(scene.world[2].bounding_box().is_none());
+        let bbox = scene.world[3].bounding_box().unwrap();
+        assert!((bbox.minimum - Point3::new(-1, -1, -3)).length() < 1e-3);
+
+        let err = parse_err(&objects.replace("v = [0, 1, 0]", "v = [2, 0, 0]"));
+        assert_eq!("objects[0]: u and v must not be parallel", err);
+        let err = parse_err(&objects.replace("normal = [0, 1, 0]", "normal = [0, 0, 0]"));
+        assert_eq!("objects[2]: normal must not be zero", err);
+        let err = parse_err(&objects.replace("max = [1, 1, -2]", "max = [1, 1, -3]"));
+        assert_eq!("objects[3]: min must be below max on every axis", err);
+    }
+
+    #[test]
+    fn test_round_objects() {
+        let objects = format!(
+            "{}\n\
+             [materials.a]\n\
+             type = \"lambertian\"\n\
+             albedo = [0.5, 0.5, 0.5]\n\
+             \n\
+             [[objects]]\n\
+             type = \"cylinder\"\n\
+             base = [0, 0, 0]\n\
+             top = [0, 2, 0]\n\
+             radius = 1\n\
+             material = \"a\"\n\
+             \n\
+             [[objects]]\n\
+             type = \"cone\"\n\
+             base = [0, 0, 0]\n\
+             apex = [0, 0, 3]\n\
+             radius = 1\n\
+             material = \"a\"\n\
+             \n\
+             [[objects]]\n\
+             type = \"capsule\"\n\
+             a = [0, 0, 0]\n\
+             b = [4, 0, 0]\n\
+             radius = 1\n\
+             material = \"a\"\n\
+             \n\
+             [[objects]]\n\
+             type = \"torus\"\n\
+             center = [0, 0, 0]\n\
+             major_radius = 2\n\
+             minor_radius = 0.5\n\
+             material = \"a\"\n",
+            CAMERA
+        );
+        let scene = parse(&objects).unwrap();
+        assert_eq!(4, scene.world.len());
+        let maxima = [
+            Point3::new(1, 2, 1),
+            Point3::new(1, 1, 3),
+            Point3::new(5, 1, 1),
+            Point3::new(2.5, 0.5, 2.5),
+        ];
+        for (object, expected) in scene.world.iter().zip(maxima) {
+            let bbox = object.bounding_box().unwrap();
+            assert!((bbox.maximum - expected).length() < 1e-9, "{:?}", bbox);
+        }
+
+        let err = parse_err(&objects.replace("apex = [0, 0, 3]", "apex = [0, 0, 0]"));
+        assert_eq!("objects[1]: the ends must differ", err);
+        let err = parse_err(&objects.replace("minor_radius = 0.5", "minor_radius = 3"));
+        assert_eq!(
+            "objects[3]: minor_radius must be positive and at most major_radius",
+            err
+        );
+    }
+
+    #[test]
+    fn test_medium() {
+        let source = format!(
+            "{}\n\
+             [materials.smoke]\n\
+             type = \"isotropic\"\n\
+             albedo = [0.2, 0.2, 0.2]\n\
+             \n\
+             [[objects]]\n\
+             type = \"constant_medium\"\n\
+             density = 0.5\n\
+             boundary = {{ type = \"sphere\", center = [0, 0, -2], radius = 1, material = \"smoke\" }}\n",
+            CAMERA
+        );
+        let scene = parse(&source).unwrap();
+        assert_eq!(1, scene.world.len());
+        let bbox = scene.world[0].bounding_box().unwrap();
+        assert_eq!(Point3::new(-1, -1, -3), bbox.minimum);
+
+        let err = parse_err(&source.replace("density = 0.5", "density = 0"));
+        assert_eq!("objects[0]: density must be positive", err);
+        let err = parse_err(&source.replace("material = \"smoke\"", "material = \"fog\""));
+        assert_eq!("objects[0]: unknown material `fog`", err);
+        let err = parse_err(&source.replace("\"isotropic\"", "\"lambertian\""));
+        assert_eq!(
+            "objects[0]: the medium's material `smoke` must be a phase function",
+            err
+        );
+    }
+
+    #[test]
+    fn test_heterogeneous_medium() {
+        let source = format!(
+            "{}\n\
+             [materials.cloud]\n\
+             type = \"henyey_greenstein\"\n\
+             albedo = [0.9, 0.9, 0.9]\n\
+             g = 0.6\n\
+             \n\
+             [[objects]]\n\
+             type = \"heterogeneous_medium\"\n\
+             absorption = 0.1\n\
+             density = {{ type = \"grid\", resolution = [2, 2, 2], values = [0, 1, 0, 1, 0, 1, 0, 1] }}\n\
+             boundary = {{ type = \"box\", min = [-1, -1, -3], max = [1, 1, -1], material = \"cloud\" }}\n\
+             \n\
+             [[objects]]\n\
+             type = \"heterogeneous_medium\"\n\
+             density = {{ type = \"noise\", seed = 2, scale = 3, max = 4 }}\n\
+             boundary = {{ type = \"sphere\", center = [3, 0, -2], radius = 1, material = \"cloud\" }}\n",
+            CAMERA
+        );
+        let scene = parse(&source).unwrap();
+        assert_eq!(2, scene.world.len());
+        let bbox = scene.world[1].bounding_box().unwrap();
+        assert_eq!(Point3::new(2, -1, -3), bbox.minimum);
+
+        let err = parse_err(&source.replace("g = 0.6", "g = 1"));
+        assert_eq!("materials.cloud: g must be between -1 and 1", err);
+        let err = parse_err(&source.replace("values = [0, 1, 0,", "values = [0, 1,"));
+        assert_eq!("objects[0]: the grid needs one value per point", err);
+        let err = parse_err(&source.replace("absorption = 0.1", "absorption = -0.1"));
+        assert_eq!(
+            "objects[0]: absorption and scattering must not be negative",
+            err
+        );
+        let err = parse_err(&source.replace(
+            "{ type = \"sphere\", center = [3, 0, -2], radius = 1, material = \"cloud\" }",
+            "{ type = \"constant_medium\", density = 1, boundary = { type = \"sphere\", center = [3, 0, -2], radius = 1, material = \"cloud\" } }",
+        ));
+        assert_eq!("objects[1]: the boundary must be a surface", err);
+    }
+
+    #[test]
+    fn test_example_scenes() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("scenes");
+        for entry in fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.extension().is_some_and(|ext| ext == "toml") {
+                if let Err(err) = Scene::load(&path) {
+                    panic!("{}: {}", path.display(), err);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_unknown_material() {
+        let err = parse_err(&format!(
+            "{}\n[[objects]]\ntype = \"sphere\"\ncenter = [0, 0, 0]\nradius = 1\nmaterial = \"nope\"\n",
+            CAMERA
+        ));
+        assert_eq!("objects[0]: unknown material `nope`", err);
+    }
+
+    #[test]
+    fn test_malformed() {
+        let err = parse_err(&format!(
+            "{}\n[materials.a]\ntype = \"lambertian\"\nalbedo = \"red\"\n",
+            CAMERA
+        ));
+        assert!(err.contains("line 6"), "{}", err);
+        assert!(err.contains("invalid type: string \"red\""), "{}", err);
+
+        let err = parse_err(&format!("{}\n[materials.a]\ntype = \"velvet\"\n", CAMERA));
+        assert!(err.contains("unknown variant `velvet`"), "{}", err);
+
+        let err = parse_err(&format!("{}\n[render]\nwidht = 100\n", CAMERA));
+        assert!(err.contains("unknown field `widht`"), "{}", err);
+
+        let err = parse_err("[render]\nwidth = 100\n");
+        assert!(err.contains("missing field `camera`"), "{}", err);
+    }
+
+    #[test]
+    fn test_invalid() {
+        let err = parse_err(&format!(
+            "{}\n[materials.a]\ntype = \"metal\"\nalbedo = [1, 1, 1]\nfuzz = 2\n",
+            CAMERA
+        ));
+        assert_eq!("materials.a: fuzz must be between 0 and 1", err);
+
+        let err = parse_err(&format!(
+            "{}\n[materials.a]\ntype = \"dielectric\"\nir = 1.5\n\n\
+             [[objects]]\ntype = \"sphere\"\ncenter = [0, 0, 0]\nradius = -1\nmaterial = \"a\"\n",
+            CAMERA
+        ));
+        assert_eq!("objects[0]: radius must be positive", err);
+
+        let err = parse_err("[camera]\nlookfrom = [0, 0, 0]\nlookat = [0, 0, 0]\nvfov = 90\n");
+        assert_eq!("camera: lookfrom and lookat must differ", err);
+
+        let err = parse_err(&format!("{}shutter = [1, 0]\n", CAMERA));
+        assert_eq!("camera: shutter must not close before it opens", err);
+
+        let err = parse_err(&format!("{}\n[render]\nheight = 0\n", CAMERA));
+        assert_eq!("render: width and height must be positive", err);
+
+        let err = parse_err(&format!(
+            "{}\n[materials.a]\ntype = \"lambertian\"\ntexture = \"wood\"\n",
+            CAMERA
+        ));
+        assert_eq!("materials.a: unknown texture `wood`", err);
+
+        let err = parse_err(&format!(
+            "{}\n[materials.a]\ntype = \"lambertian\"\n",
+            CAMERA
+        ));
+        assert_eq!("materials.a: needs either an albedo or a texture", err);
+
+        let err = parse_err(&format!(
+            "{}\n[textures.a]\ntype = \"image\"\npath = \"missing.png\"\nwrap = \"mirror\"\n",
+            CAMERA
+        ));
+        assert!(err.starts_with("./missing.png: "), "{}", err);
+    }
+}
@@ -0,0 +1,117 @@
+//! Scenes that are available without a scene file
+
+use rand::{prelude::SmallRng, Rng};
+
+use crate::{
+    background::Gradient,
+    camera::Camera,
+    hittable::{Hittable, HittableList, Sphere},
+    material::{Dielectric, Lambertian, Metal},
+    renderer::RenderSettings,
+    vec3::{Color, Point3, Vec3},
+};
+
+use super::Scene;
+
+/// The final scene of "Ray Tracing in One Weekend", with hundreds of small
+/// random spheres around three large ones
+pub fn random(rng: &mut SmallRng, settings: RenderSettings) -> Scene {
+    let mut world = large_spheres();
+    world.extend(random_spheres(rng));
+
+    Scene {
+        world,
+        lights: vec![],
+        camera: final_camera(&settings),
+        settings,
+        background: Box::new(Gradient::sky()),
+    }
+}
+
+/// Only the three large spheres of the final scene
+pub fn three_spheres(settings: RenderSettings) -> Scene {
+    Scene {
+        world: large_spheres(),
+        lights: vec![],
+        camera: final_camera(&settings),
+        settings,
+        background: Box::new(Gradient::sky()),
+    }
+}
+
+fn final_camera(settings: &RenderSettings) -> Camera {
+    let lookfrom = Point3::new(13, 2, 3);
+    let lookat = Point3::new(0, 0, 0);
+    let vup = Vec3::new(0, 1, 0);
+    let focus_dist = 10.0;
+    let aperture = 0.1;
+
+    Camera::new(
+        lookfrom,
+        lookat,
+        vup,
+        20.0,
+        settings.aspect_ratio(),
+        aperture,
+        focus_dist,
+    )
+}
+
+fn large_spheres() -> HittableList {
+    vec![
+        // Floor
+        Box::new(Sphere::new(
+            Point3::new(0, -1000, 0),
+            1000.0,
+            Lambertian::new(Color::new(0.5, 0.5, 0.5)),
+        )),
+        // three spheres
+        Box::new(Sphere::new(Point3::new(0, 1, 0), 1.0, Dielectric::new(1.5))),
+        Box::new(Sphere::new(
+            Point3::new(-4, 1, 0),
+            1.0,
+            Lambertian::new(Color::new(0.4, 0.2, 0.1)),
+        )),
+        Box::new(Sphere::new(
+            Point3::new(4, 1, 0),
+            1.0,
+            Metal::new(Color::new(0.7, 0.6, 0.5), 0.0),
+        )),
+    ]
+}
+
+fn random_spheres(rng: &mut SmallRng) -> HittableList {
+    let mut world: HittableList = vec![];
+
+    for a in -11..11 {
+        for b in -11..11 {
+            let (a, b) = (a as f64, b as f64);
+            let choose_mat: f64 = rng.gen();
+            let center = Point3::new(a + 0.9 * rng.gen::<f64>(), 0.2, b + 0.9 * rng.gen::<f64>());
+
+            if (center - Vec3::new(4, 0.2, 0)).length() > 0.9 {
+                let sphere: Box<dyn Hittable + Send + Sync> = match choose_mat {
+                    x if x < 0.8 => {
+                        // Diffuse
+                        let albedo = Color::new_random(rng) * Color::new_random(rng);
+                        Box::new(Sphere::new(center, 0.2, Lambertian::new(albedo)))
+                    }
+                    x if x < 0.95 => {
+                        // Metal
+                        let albedo = Color::new_random_range(rng, 0.5, 1.0);
+                        let fuzz = rng.gen_range(0.0..0.5);
+                        Box::new(Sphere::new(center, 0.2, Metal::new(albedo, fuzz)))
+                    }
+                    _ => {
+                        // Glass
+                        Box::new(Sphere::new(center, 0.2, Dielectric::new(1.5)))
+                    }
+                };
+
+                world.push(sphere);
+            }
+        }
+    }
+
+    world
+}
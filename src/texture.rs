@@ -0,0 +1,31 @@
+//! Colors that vary over a surface
+
+use std::sync::Arc;
+
+use crate::vec3::{Color, Point3};
+
+mod checker;
+pub use checker::*;
+mod image_texture;
+pub use image_texture::*;
+mod noise;
+pub use noise::*;
+
+pub trait Texture {
+    /// Color at surface coordinates `uv` and point `p`
+    fn value(&self, uv: (f64, f64), p: Point3) -> Color;
+}
+
+/// A single color everywhere
+impl Texture for Color {
+    fn value(&self, _uv: (f64, f64), _p: Point3) -> Color {
+        *self
+    }
+}
+
+/// Allows one texture to be shared between many materials
+impl<T: Texture + ?Sized> Texture for Arc<T> {
+    fn value(&self, uv: (f64, f64), p: Point3) -> Color {
+        (**self).value(uv, p)
+    }
+}
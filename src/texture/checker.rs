@@ -0,0 +1,48 @@
+use crate::vec3::{Color, Point3};
+
+use super::Texture;
+
+/// Alternates between two textures in a 3D grid of cubes
+#[derive(Clone, Copy)]
+pub struct Checker<E: Texture, O: Texture> {
+    even: E,
+    odd: O,
+    /// Edge length of the cubes
+    scale: f64,
+}
+
+impl<E: Texture, O: Texture> Checker<E, O> {
+    pub fn new(even: E, odd: O, scale: f64) -> Self {
+        Self { even, odd, scale }
+    }
+}
+
+impl<E: Texture, O: Texture> Texture for Checker<E, O> {
+    fn value(&self, uv: (f64, f64), p: Point3) -> Color {
+        let cell = |c: f64| (c / self.scale).floor() as i64;
+        if (cell(p.x()) + cell(p.y()) + cell(p.z())).rem_euclid(2) == 0 {
+            self.even.value(uv, p)
+        } else {
+            self.odd.value(uv, p)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checker() {
+        let black = Color::new(0, 0, 0);
+        let white = Color::new(1, 1, 1);
+        let checker = Checker::new(white, black, 2.0);
+        let value = |x, y, z| checker.value((0.0, 0.0), Point3::new(x, y, z));
+
+        assert_eq!(white, value(0.5, 0.5, 0.5));
+        assert_eq!(black, value(2.5, 0.5, 0.5));
+        assert_eq!(black, value(-0.5, 0.5, 0.5));
+        assert_eq!(white, value(-0.5, -0.5, 0.5));
+        assert_eq!(white, value(2.5, 2.5, 1.5));
+    }
+}
@@ -0,0 +1,283 @@
+use std::{
+    fmt::Display,
+    path::{Path, PathBuf},
+};
+
+use serde::Deserialize;
+
+use crate::vec3::{Color, Point3};
+
+use super::Texture;
+
+#[derive(Debug)]
+pub enum Error {
+    Load {
+        path: PathBuf,
+        source: image::ImageError,
+    },
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Load { path, source } => write!(f, "{}: {}", path.display(), source),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Load { source, .. } => Some(source),
+        }
+    }
+}
+
+/// How to pick a color between the centers of texels
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Filter {
+    /// The closest texel
+    Nearest,
+    /// Weighted average of the four closest texels
+    #[default]
+    Bilinear,
+}
+
+/// How to handle coordinates outside of `[0, 1]`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Wrap {
+    /// Tile the image
+    #[default]
+    Repeat,
+    /// Extend the edge texels
+    Clamp,
+    /// Tile the image, flipping every other copy
+    Mirror,
+}
+
+impl Wrap {
+    fn apply(self, i: i64, size: usize) -> usize {
+        let n = size as i64;
+        let i = match self {
+            Wrap::Repeat => i.rem_euclid(n),
+            Wrap::Clamp => i.clamp(0, n - 1),
+            Wrap::Mirror => match i.rem_euclid(2 * n) {
+                i if i < n => i,
+                i => 2 * n - 1 - i,
+            },
+        };
+        i as usize
+    }
+}
+
+/// Texture mapped from an image using the surface's UV coordinates, with
+/// `v` pointing up
+pub struct ImageTexture {
+    width: usize,
+    height: usize,
+    /// Linear colors, row by row from the top
+    pixels: Vec<Color>,
+    filter: Filter,
+    wrap: Wrap,
+}
+
+impl ImageTexture {
+    /// Load an image. 8 and 16 bit images are assumed to be sRGB encoded and
+    /// converted to linear colors, floating point images are used as is.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let image = image::open(path).map_err(|source| Error::Load {
+            path: path.to_path_buf(),
+            source,
+        })?;
+
+        let linear = matches!(
+            image.color(),
+            image::ColorType::Rgb32F | image::ColorType::Rgba32F
+        );
+        let image = image.into_rgb32f();
+        let decode = |c: f32| {
+            if linear {
+                c as f64
+            } else {
+                srgb_to_linear(c as f64)
+            }
+        };
+        let pixels = image
+            .pixels()
+            .map(|p| Color::new(decode(p[0]), decode(p[1]), decode(p[2])))
+            .collect();
+
+        Ok(Self::new(
+            image.width() as usize,
+            image.height() as usize,
+            pixels,
+        ))
+    }
+
+    /// Texture from linear colors, given row by row from the top. Panics if
+    /// the number of pixels doesn't match the size.
+    pub fn new(width: usize, height: usize, pixels: Vec<Color>) -> Self {
+        assert!(width > 0 && height > 0, "texture must not be empty");
+        assert_eq!(width * height, pixels.len());
+        Self {
+            width,
+            height,
+            pixels,
+            filter: Filter::default(),
+            wrap: Wrap::default(),
+        }
+    }
+
+    pub fn with_filter(mut self, filter: Filter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    pub fn with_wrap(mut self, wrap: Wrap) -> Self {
+        self.wrap = wrap;
+        self
+    }
+
+    fn texel(&self, x: i64, y: i64) -> Color {
+        let x = self.wrap.apply(x, self.width);
+        let y = self.wrap.apply(y, self.height);
+        self.pixels[y * self.width + x]
+    }
+}
+
+impl Texture for ImageTexture {
+    fn value(&self, (u, v): (f64, f64), _p: Point3) -> Color {
+        // Continuous texel coordinates, with the image's first row at the top
+        let x = u * self.width as f64;
+        let y = (1.0 - v) * self.height as f64;
+
+        match self.filter {
+            Filter::Nearest => self.texel(x.floor() as i64, y.floor() as i64),
+            Filter::Bilinear => {
+                let (x, y) = (x - 0.5, y - 0.5);
+                let (x0, y0) = (x.floor(), y.floor());
+                let (fx, fy) = (x - x0, y - y0);
+                // Huge coordinates saturate the cast, so the neighbours must too
+                let (x0, y0) = (x0 as i64, y0 as i64);
+                let (x1, y1) = (x0.saturating_add(1), y0.saturating_add(1));
+
+                let top = (1.0 - fx) * self.texel(x0, y0) + fx * self.texel(x1, y0);
+                let bottom = (1.0 - fx) * self.texel(x0, y1) + fx * self.texel(x1, y1);
+                (1.0 - fy) * top + fy * bottom
+            }
+        }
+    }
+}
+
+/// Decode an sRGB encoded channel value in `[0, 1]`
+fn srgb_to_linear(c: f64) -> f64 {
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 2x2 texture, black in the top left and white in the bottom right
+    fn texture() -> ImageTexture {
+        ImageTexture::new(
+            2,
+            2,
+            vec![
+                Color::new(0, 0, 0),
+                Color::new(1, 0, 0),
+                Color::new(0, 1, 0),
+                Color::new(1, 1, 1),
+            ],
+        )
+    }
+
+    fn value(texture: &ImageTexture, u: f64, v: f64) -> Color {
+        texture.value((u, v), Point3::new(0, 0, 0))
+    }
+
+    #[test]
+    fn test_nearest() {
+        let texture = texture().with_filter(Filter::Nearest);
+        assert_eq!(Color::new(0, 0, 0), value(&texture, 0.25, 0.75));
+        assert_eq!(Color::new(1, 0, 0), value(&texture, 0.75, 0.75));
+        assert_eq!(Color::new(0, 1, 0), value(&texture, 0.25, 0.25));
+        // Repeats by default
+        assert_eq!(Color::new(1, 1, 1), value(&texture, 1.75, -0.75));
+    }
+
+    #[test]
+    fn test_bilinear() {
+        let texture = texture();
+        // Texel centers
+        assert_eq!(Color::new(0, 0, 0), value(&texture, 0.25, 0.75));
+        assert_eq!(Color::new(1, 1, 1), value(&texture, 0.75, 0.25));
+        // Halfway between all four
+        assert_eq!(Color::new(0.5, 0.5, 0.25), value(&texture, 0.5, 0.5));
+        // Across the edge, blending with the other side
+        assert_eq!(Color::new(0.5, 0, 0), value(&texture, 0.0, 0.75));
+
+        let texture = texture.with_wrap(Wrap::Clamp);
+        assert_eq!(Color::new(0, 0, 0), value(&texture, 0.0, 0.75));
+    }
+
+    #[test]
+    fn test_huge_uv() {
+        for wrap in [Wrap::Repeat, Wrap::Clamp, Wrap::Mirror] {
+            for filter in [Filter::Nearest, Filter::Bilinear] {
+                let texture = texture().with_wrap(wrap).with_filter(filter);
+                for (u, v) in [(1e300, 0.5), (0.5, -1e300), (-1e20, 1e20)] {
+                    let color = value(&texture, u, v);
+                    assert!(color.x().is_finite(), "{:?}", color);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_wrap() {
+        assert_eq!(
+            vec![2, 0, 1, 2, 0],
+            [-1, 0, 1, 2, 3].map(|i| Wrap::Repeat.apply(i, 3)).to_vec()
+        );
+        assert_eq!(
+            vec![0, 0, 1, 2, 2],
+            [-1, 0, 1, 2, 3].map(|i| Wrap::Clamp.apply(i, 3)).to_vec()
+        );
+        assert_eq!(
+            vec![1, 0, 0, 1, 2, 2, 1],
+            [-2, -1, 0, 1, 2, 3, 4]
+                .map(|i| Wrap::Mirror.apply(i, 3))
+                .to_vec()
+        );
+    }
+
+    #[test]
+    fn test_load() {
+        let path = std::env::temp_dir().join(format!("raytracing-{}.png", std::process::id()));
+        image::RgbImage::from_raw(2, 1, vec![0, 128, 255, 255, 255, 255])
+            .unwrap()
+            .save(&path)
+            .unwrap();
+        let texture = ImageTexture::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(2, texture.width);
+        assert_eq!(1, texture.height);
+        let [black, gray, white] = [
+            texture.pixels[0].x(),
+            texture.pixels[0].y(),
+            texture.pixels[1].x(),
+        ];
+        assert_eq!(0.0, black);
+        assert!((gray - 0.2159).abs() < 1e-3, "{}", gray);
+        assert_eq!(1.0, white);
+    }
+}
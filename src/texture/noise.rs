@@ -0,0 +1,279 @@
+use rand::{prelude::SmallRng, seq::SliceRandom, SeedableRng};
+use serde::Deserialize;
+
+use crate::{
+    random_unit_vector,
+    vec3::{Color, Point3, Vec3},
+};
+
+use super::Texture;
+
+const POINT_COUNT: usize = 256;
+
+/// Gradient noise generator
+#[derive(Clone)]
+pub struct Perlin {
+    gradients: Vec<Vec3>,
+    perm_x: Vec<usize>,
+    perm_y: Vec<usize>,
+    perm_z: Vec<usize>,
+}
+
+impl Perlin {
+    /// Generators with the same seed produce the same noise
+    pub fn new(seed: u64) -> Self {
+        let mut rng = SmallRng::seed_from_u64(seed);
+        let gradients = (0..POINT_COUNT)
+            .map(|_| random_unit_vector(&mut rng))
+            .collect();
+        let mut permutation = || {
+            let mut p: Vec<usize> = (0..POINT_COUNT).collect();
+            p.shuffle(&mut rng);
+            p
+        };
+
+        Self {
+            gradients,
+            perm_x: permutation(),
+            perm_y: permutation(),
+            perm_z: permutation(),
+        }
+    }
+
+    /// Smooth noise in `[-1, 1]`, varying over distances of about 1
+    pub fn noise(&self, p: Point3) -> f64 {
+        let floor = [p.x().floor(), p.y().floor(), p.z().floor()];
+        let [u, v, w] = [p.x() - floor[0], p.y() - floor[1], p.z() - floor[2]];
+        let [i, j, k] = floor.map(|f| f as i64);
+
+        // Hermite smoothing hides the grid
+        let [uu, vv, ww] = [u, v, w].map(|t| t * t * (3.0 - 2.0 * t));
+
+        let mut accum = 0.0;
+        for di in 0..2 {
+            for dj in 0..2 {
+                for dk in 0..2 {
+                    let index = self.perm_x[((i + di) & 255) as usize]
+                        ^ self.perm_y[((j + dj) & 255) as usize]
+                        ^ self.perm_z[((k + dk) & 255) as usize];
+                    let (fi, fj, fk) = (di as f64, dj as f64, dk as f64);
+                    let weight = Vec3::new(u - fi, v - fj, w - fk);
+                    accum += (fi * uu + (1.0 - fi) * (1.0 - uu))
+                        * (fj * vv + (1.0 - fj) * (1.0 - vv))
+                        * (fk * ww + (1.0 - fk) * (1.0 - ww))
+                        * self.gradients[index].dot(&weight);
+                }
+            }
+        }
+
+        accum
+    }
+
+    /// Sum of `octaves` layers of noise, each at twice the frequency and half
+    /// the amplitude of the previous one
+    pub fn turbulence(&self, p: Point3, octaves: u32) -> f64 {
+        let mut accum = 0.0;
+        let mut p = p;
+        let mut weight = 1.0;
+
+        for _ in 0..octaves {
+            accum += weight * self.noise(p);
+            weight *= 0.5;
+            p = 2.0 * p;
+        }
+
+        accum.abs()
+    }
+}
+
+/// Procedural pattern of a `Noise` texture
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Pattern {
+    /// Plain Perlin noise
+    Perlin,
+    /// Several octaves of Perlin noise
+    Turbulence,
+    /// Stripes along the z axis, distorted by turbulence
+    Marble,
+    /// Rings around the y axis, distorted by turbulence
+    Wood,
+    /// Distance to the closest of randomly scattered points, giving cells
+    Worley,
+}
+
+const TURBULENCE_OCTAVES: u32 = 7;
+
+/// Texture blending between two colors following a procedural pattern
+#[derive(Clone)]
+pub struct Noise {
+    pattern: Pattern,
+    seed: u64,
+    perlin: Perlin,
+    /// Frequency of the pattern
+    scale: f64,
+    colors: [Color; 2],
+}
+
+impl Noise {
+    /// Black to white noise. Textures with the same seed look the same.
+    pub fn new(pattern: Pattern, seed: u64) -> Self {
+        Self {
+            pattern,
+            seed,
+            perlin: Perlin::new(seed),
+            scale: 1.0,
+            colors: [Color::new(0, 0, 0), Color::new(1, 1, 1)],
+        }
+    }
+
+    pub fn with_scale(mut self, scale: f64) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    /// Colors for the low and high values of the pattern
+    pub fn with_colors(mut self, low: Color, high: Color) -> Self {
+        self.colors = [low, high];
+        self
+    }
+
+    /// Pattern value at `p`, in `[0, 1]`
+    fn pattern_value(&self, p: Point3) -> f64 {
+        let p = self.scale * p;
+        let turbulence = || self.perlin.turbulence(p, TURBULENCE_OCTAVES);
+
+        let t = match self.pattern {
+            Pattern::Perlin => 0.5 * (1.0 + self.perlin.noise(p)),
+            Pattern::Turbulence => turbulence(),
+            Pattern::Marble => 0.5 * (1.0 + (p.z() + 10.0 * turbulence()).sin()),
+            Pattern::Wood => ((p.x() * p.x() + p.z() * p.z()).sqrt() + turbulence()).fract(),
+            Pattern::Worley => worley(p, self.seed),
+        };
+        t.clamp(0.0, 1.0)
+    }
+}
+
+impl Texture for Noise {
+    fn value(&self, _uv: (f64, f64), p: Point3) -> Color {
+        let t = self.pattern_value(p);
+        (1.0 - t) * self.colors[0] + t * self.colors[1]
+    }
+}
+
+/// Distance from `p` to the closest feature point, with one feature point in
+/// each unit cube
+fn worley(p: Point3, seed: u64) -> f64 {
+    let cell = [p.x().floor(), p.y().floor(), p.z().floor()];
+    let mut closest = f64::INFINITY;
+
+    for dx in -1..=1 {
+        for dy in -1..=1 {
+            for dz in -1..=1 {
+                let neighbor = [
+                    cell[0] + dx as f64,
+                    cell[1] + dy as f64,
+                    cell[2] + dz as f64,
+                ];
+                let mut hash = seed;
+                for c in neighbor {
+                    hash = split_mix(hash ^ c as i64 as u64);
+                }
+                let offset = [0, 1, 2].map(|i| {
+                    hash = split_mix(hash.wrapping_add(i));
+                    (hash >> 11) as f64 / (1u64 << 53) as f64
+                });
+
+                let feature = Point3::new(
+                    neighbor[0] + offset[0],
+                    neighbor[1] + offset[1],
+                    neighbor[2] + offset[2],
+                );
+                closest = closest.min((feature - p).length());
+            }
+        }
+    }
+
+    closest
+}
+
+/// SplitMix64 finalizer, scrambling the bits of `x`
+fn split_mix(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PATTERNS: [Pattern; 5] = [
+        Pattern::Perlin,
+        Pattern::Turbulence,
+        Pattern::Marble,
+        Pattern::Wood,
+        Pattern::Worley,
+    ];
+
+    fn points() -> impl Iterator<Item = Point3> {
+        (0..200).map(|i| {
+            let i = i as f64;
+            Point3::new(0.37 * i, -0.21 * i, 0.13 * i - 5.0)
+        })
+    }
+
+    #[test]
+    fn test_perlin() {
+        let perlin = Perlin::new(1);
+        for p in points() {
+            let noise = perlin.noise(p);
+            assert!((-1.0..=1.0).contains(&noise), "{}", noise);
+        }
+        // Zero on the lattice
+        assert_eq!(0.0, perlin.noise(Point3::new(3, -2, 7)));
+        // Smooth
+        let p = Point3::new(0.3, 0.4, 0.5);
+        let q = p + Vec3::new(1e-6, 0, 0);
+        assert!((perlin.noise(p) - perlin.noise(q)).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_deterministic() {
+        for pattern in PATTERNS {
+            let a = Noise::new(pattern, 7).with_scale(3.0);
+            let b = Noise::new(pattern, 7).with_scale(3.0);
+            let c = Noise::new(pattern, 8).with_scale(3.0);
+            let values = |texture: &Noise| {
+                points()
+                    .map(|p| texture.value((0.0, 0.0), p))
+                    .collect::<Vec<_>>()
+            };
+            assert_eq!(values(&a), values(&b), "{:?}", pattern);
+            assert_ne!(values(&a), values(&c), "{:?}", pattern);
+        }
+    }
+
+    #[test]
+    fn test_range() {
+        let low = Color::new(0.1, 0.2, 0.3);
+        let high = Color::new(0.9, 0.5, 0.3);
+        for pattern in PATTERNS {
+            let texture = Noise::new(pattern, 1).with_colors(low, high);
+            for p in points() {
+                let c = texture.value((0.0, 0.0), p);
+                assert!(c.x() >= 0.1 && c.x() <= 0.9, "{:?} {:?}", pattern, c);
+                assert!(c.y() >= 0.2 && c.y() <= 0.5, "{:?} {:?}", pattern, c);
+            }
+        }
+    }
+
+    #[test]
+    fn test_worley() {
+        // Continuous across cell boundaries
+        let a = worley(Point3::new(1.0 - 1e-9, 0.5, 0.5), 3);
+        let b = worley(Point3::new(1.0 + 1e-9, 0.5, 0.5), 3);
+        assert!((a - b).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,279 @@
+//! Affine transformations as 4x4 matrices
+
+use std::ops::Mul;
+
+use crate::{
+    degrees_to_radians,
+    hittable::Aabb,
+    ray::Ray,
+    vec3::{Point3, Vec3},
+};
+
+/// Row-major 4x4 matrix
+pub type Matrix = [[f64; 4]; 4];
+
+const IDENTITY: Matrix = [
+    [1.0, 0.0, 0.0, 0.0],
+    [0.0, 1.0, 0.0, 0.0],
+    [0.0, 0.0, 1.0, 0.0],
+    [0.0, 0.0, 0.0, 1.0],
+];
+
+fn multiply(a: &Matrix, b: &Matrix) -> Matrix {
+    let mut m = [[0.0; 4]; 4];
+    for (i, row) in m.iter_mut().enumerate() {
+        for (j, value) in row.iter_mut().enumerate() {
+            *value = (0..4).map(|k| a[i][k] * b[k][j]).sum();
+        }
+    }
+    m
+}
+
+fn transpose(m: &Matrix) -> Matrix {
+    let mut t = [[0.0; 4]; 4];
+    for (i, row) in m.iter().enumerate() {
+        for (j, &value) in row.iter().enumerate() {
+            t[j][i] = value;
+        }
+    }
+    t
+}
+
+/// An affine transformation together with its inverse.
+///
+/// Transformations are combined with `*`, where `a * b` applies `b` first:
+///
+/// ```
+/// use raytracing::{transform::Transform, vec3::{Point3, Vec3}};
+///
+/// let t = Transform::translate(Vec3::new(1, 0, 0)) * Transform::scale(Vec3::new(2, 2, 2));
+/// assert_eq!(Point3::new(3, 2, 2), t.apply_point(Point3::new(1, 1, 1)));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform {
+    matrix: Matrix,
+    inverse: Matrix,
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl Transform {
+    pub const fn identity() -> Self {
+        Self {
+            matrix: IDENTITY,
+            inverse: IDENTITY,
+        }
+    }
+
+    pub fn translate(offset: Vec3) -> Self {
+        let mut matrix = IDENTITY;
+        let mut inverse = IDENTITY;
+        for (i, c) in [offset.x(), offset.y(), offset.z()].into_iter().enumerate() {
+            matrix[i][3] = c;
+            inverse[i][3] = -c;
+        }
+        Self { matrix, inverse }
+    }
+
+    /// Scale along the axes. Panics if any factor is zero.
+    pub fn scale(factors: Vec3) -> Self {
+        let mut matrix = IDENTITY;
+        let mut inverse = IDENTITY;
+        for (i, c) in [factors.x(), factors.y(), factors.z()]
+            .into_iter()
+            .enumerate()
+        {
+            assert!(c != 0.0, "scale factors must not be zero");
+            matrix[i][i] = c;
+            inverse[i][i] = 1.0 / c;
+        }
+        Self { matrix, inverse }
+    }
+
+    /// Rotate counter-clockwise around `axis`, looking against it
+    pub fn rotate(axis: Vec3, degrees: f64) -> Self {
+        let a = axis.unit_vector();
+        let (x, y, z) = (a.x(), a.y(), a.z());
+        let (sin, cos) = degrees_to_radians(degrees).sin_cos();
+        let c = 1.0 - cos;
+
+        let matrix = [
+            [
+                cos + x * x * c,
+                x * y * c - z * sin,
+                x * z * c + y * sin,
+                0.0,
+            ],
+            [
+                y * x * c + z * sin,
+                cos + y * y * c,
+                y * z * c - x * sin,
+                0.0,
+            ],
+            [
+                z * x * c - y * sin,
+                z * y * c + x * sin,
+                cos + z * z * c,
+                0.0,
+            ],
+            [0.0, 0.0, 0.0, 1.0],
+        ];
+        // Rotations are orthogonal
+        let inverse = transpose(&matrix);
+        Self { matrix, inverse }
+    }
+
+    pub fn rotate_x(degrees: f64) -> Self {
+        Self::rotate(Vec3::new(1, 0, 0), degrees)
+    }
+
+    pub fn rotate_y(degrees: f64) -> Self {
+        Self::rotate(Vec3::new(0, 1, 0), degrees)
+    }
+
+    pub fn rotate_z(degrees: f64) -> Self {
+        Self::rotate(Vec3::new(0, 0, 1), degrees)
+    }
+
+    pub fn matrix(&self) -> &Matrix {
+        &self.matrix
+    }
+
+    pub fn inverse(&self) -> Self {
+        Self {
+            matrix: self.inverse,
+            inverse: self.matrix,
+        }
+    }
+
+    pub fn apply_point(&self, p: Point3) -> Point3 {
+        let m = &self.matrix;
+        let [x, y, z] =
+            [0, 1, 2].map(|i| m[i][0] * p.x() + m[i][1] * p.y() + m[i][2] * p.z() + m[i][3]);
+        Point3::new(x, y, z)
+    }
+
+    pub fn apply_vector(&self, v: Vec3) -> Vec3 {
+        let m = &self.matrix;
+        let [x, y, z] = [0, 1, 2].map(|i| m[i][0] * v.x() + m[i][1] * v.y() + m[i][2] * v.z());
+        Vec3::new(x, y, z)
+    }
+
+    /// Transform a surface normal, which needs the inverse transpose to stay
+    /// perpendicular to the surface. The result is not normalized.
+    pub fn apply_normal(&self, n: Vec3) -> Vec3 {
+        let m = &self.inverse;
+        let [x, y, z] = [0, 1, 2].map(|i| m[0][i] * n.x() + m[1][i] * n.y() + m[2][i] * n.z());
+        Vec3::new(x, y, z)
+    }
+
+    /// Transform origin and direction. The direction is not normalized, so
+    /// distances along the ray stay the same.
+    pub fn apply_ray(&self, ray: Ray) -> Ray {
+        Ray {
+            origin: self.apply_point(ray.origin),
+            direction: self.apply_vector(ray.direction),
+            time: ray.time,
+        }
+    }
+
+    /// Box enclosing the transformed box
+    pub fn apply_bounds(&self, bbox: &Aabb) -> Aabb {
+        let (min, max) = (bbox.minimum, bbox.maximum);
+        (0..8)
+            .map(|corner| {
+                self.apply_point(Point3::new(
+                    if corner & 1 == 0 { min.x() } else { max.x() },
+                    if corner & 2 == 0 { min.y() } else { max.y() },
+                    if corner & 4 == 0 { min.z() } else { max.z() },
+                ))
+            })
+            .fold(Aabb::empty(), |acc, p| acc.surrounding(&Aabb::new(p, p)))
+    }
+}
+
+/// `a * b` applies `b` first, then `a`
+impl Mul for Transform {
+    type Output = Transform;
+
+    fn mul(self, rhs: Transform) -> Transform {
+        Transform {
+            matrix: multiply(&self.matrix, &rhs.matrix),
+            inverse: multiply(&rhs.inverse, &self.inverse),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_near(expected: Vec3, actual: Vec3) {
+        assert!(
+            (expected - actual).length() < 1e-9,
+            "{:?} != {:?}",
+            expected,
+            actual
+        );
+    }
+
+    #[test]
+    fn test_translate() {
+        let t = Transform::translate(Vec3::new(1, 2, 3));
+        assert_eq!(Point3::new(1, 3, 5), t.apply_point(Point3::new(0, 1, 2)));
+        // Directions are not moved
+        assert_eq!(Vec3::new(0, 1, 2), t.apply_vector(Vec3::new(0, 1, 2)));
+    }
+
+    #[test]
+    fn test_rotate() {
+        let t = Transform::rotate_y(90.0);
+        assert_near(Vec3::new(0, 0, -1), t.apply_vector(Vec3::new(1, 0, 0)));
+        let t = Transform::rotate_z(90.0);
+        assert_near(Vec3::new(0, 1, 0), t.apply_vector(Vec3::new(1, 0, 0)));
+        let t = Transform::rotate_x(90.0);
+        assert_near(Vec3::new(0, 0, 1), t.apply_vector(Vec3::new(0, 1, 0)));
+        let t = Transform::rotate(Vec3::new(1, 1, 1), 120.0);
+        assert_near(Vec3::new(0, 1, 0), t.apply_vector(Vec3::new(1, 0, 0)));
+    }
+
+    #[test]
+    fn test_compose() {
+        let t = Transform::translate(Vec3::new(1, 0, 0))
+            * Transform::rotate_z(90.0)
+            * Transform::scale(Vec3::new(2, 1, 1));
+        let p = Point3::new(1, 1, 1);
+        assert_near(Point3::new(0, 2, 1), t.apply_point(p));
+        assert_near(p, t.inverse().apply_point(t.apply_point(p)));
+
+        let product = multiply(t.matrix(), &t.inverse().matrix);
+        for (i, row) in product.iter().enumerate() {
+            for (j, value) in row.iter().enumerate() {
+                assert!((value - IDENTITY[i][j]).abs() < 1e-12);
+            }
+        }
+    }
+
+    #[test]
+    fn test_normal() {
+        // Squashing a 45° slope makes its normal steeper
+        let t = Transform::scale(Vec3::new(1, 0.5, 1));
+        let tangent = t.apply_vector(Vec3::new(1, 1, 0));
+        let normal = t.apply_normal(Vec3::new(-1, 1, 0));
+        assert!(tangent.dot(&normal).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_bounds() {
+        let bbox = Aabb::new(Point3::new(-1, -1, -1), Point3::new(1, 1, 1));
+        let t = Transform::translate(Vec3::new(5, 0, 0)) * Transform::rotate_y(45.0);
+        let bounds = t.apply_bounds(&bbox);
+        let r = 2.0f64.sqrt();
+        assert_near(Point3::new(5.0 - r, -1.0, -r), bounds.minimum);
+        assert_near(Point3::new(5.0 + r, 1.0, r), bounds.maximum);
+    }
+}
@@ -9,6 +9,8 @@ use crate::random_in_unit_sphere;
 
 pub mod color;
 pub use color::*;
+pub mod onb;
+pub use onb::*;
 pub mod point3;
 pub use point3::*;
 
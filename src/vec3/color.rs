@@ -4,22 +4,40 @@ use super::Vec3;
 
 pub type Color = Vec3;
 
-impl Display for Color {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl Color {
+    /// Relative luminance, using the Rec. 709 primaries
+    pub fn luminance(self) -> f64 {
+        0.2126 * self.0 + 0.7152 * self.1 + 0.0722 * self.2
+    }
+
+    /// Convert a linear color to 8 bits per channel, gamma-corrected for
+    /// gamma=2.0 (a close approximation of sRGB).
+    pub fn to_rgb8(self) -> [u8; 3] {
         let Vec3(r, g, b) = self;
+        [r, g, b].map(|c| (256.0 * c.sqrt().clamp(0.0, 0.999)) as u8)
+    }
 
-        // gamma-correct for gamma=2.0
-        let r = r.sqrt();
-        let g = g.sqrt();
-        let b = b.sqrt();
+    /// Convert a linear color to 8 bits per channel, encoded with the sRGB
+    /// transfer function
+    pub fn to_srgb8(self) -> [u8; 3] {
+        let Vec3(r, g, b) = self;
+        [r, g, b].map(|c| (255.0 * srgb_encode(c.clamp(0.0, 1.0))).round() as u8)
+    }
+}
+
+/// The sRGB transfer function, from linear to encoded values in `[0, 1]`
+fn srgb_encode(c: f64) -> f64 {
+    if c <= 0.0031308 {
+        12.92 * c
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    }
+}
 
-        write!(
-            f,
-            "{} {} {}",
-            (256.0 * r.clamp(0.0, 0.999)) as i32,
-            (256.0 * g.clamp(0.0, 0.999)) as i32,
-            (256.0 * b.clamp(0.0, 0.999)) as i32,
-        )
+impl Display for Color {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let [r, g, b] = self.to_rgb8();
+        write!(f, "{} {} {}", r, g, b)
     }
 }
 
@@ -32,4 +50,25 @@ mod tests {
         let c = Color::new(0, 0.5, 1);
         assert_eq!("0 181 255", format!("{}", c));
     }
+
+    #[test]
+    fn test_luminance() {
+        assert_eq!(0.0, Color::new(0, 0, 0).luminance());
+        assert!((Color::new(1, 1, 1).luminance() - 1.0).abs() < 1e-12);
+        assert_eq!(0.7152, Color::new(0, 1, 0).luminance());
+    }
+
+    #[test]
+    fn test_to_rgb8() {
+        assert_eq!([0, 181, 255], Color::new(0, 0.5, 1).to_rgb8());
+        assert_eq!([0, 0, 255], Color::new(-1, f64::NAN, 10).to_rgb8());
+    }
+
+    #[test]
+    fn test_to_srgb8() {
+        assert_eq!([0, 188, 255], Color::new(0, 0.5, 1).to_srgb8());
+        // The linear segment near black
+        assert_eq!([3, 13, 137], Color::new(0.001, 0.004, 0.25).to_srgb8());
+        assert_eq!([0, 0, 255], Color::new(-1, f64::NAN, 10).to_srgb8());
+    }
 }
@@ -0,0 +1,56 @@
+use super::Vec3;
+
+/// Orthonormal basis, for working in coordinates relative to a surface
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Onb {
+    pub u: Vec3,
+    pub v: Vec3,
+    pub w: Vec3,
+}
+
+impl Onb {
+    /// Basis with `w` pointing along `n`, and `u` and `v` chosen arbitrarily
+    pub fn from_w(n: Vec3) -> Self {
+        let w = n.unit_vector();
+        let a = if w.x().abs() > 0.9 {
+            Vec3::new(0, 1, 0)
+        } else {
+            Vec3::new(1, 0, 0)
+        };
+        let v = w.cross(a).unit_vector();
+        let u = v.cross(w);
+        Self { u, v, w }
+    }
+
+    /// Convert coordinates in this basis to world coordinates
+    pub fn local(&self, a: Vec3) -> Vec3 {
+        a.x() * self.u + a.y() * self.v + a.z() * self.w
+    }
+
+    /// Coordinates of the world vector `a` in this basis, the inverse of
+    /// `local`
+    pub fn coordinates(&self, a: Vec3) -> Vec3 {
+        Vec3::new(a.dot(&self.u), a.dot(&self.v), a.dot(&self.w))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_w() {
+        for n in [Vec3::new(0, 0, 2), Vec3::new(1, 0, 0), Vec3::new(1, -2, 3)] {
+            let onb = Onb::from_w(n);
+            assert!((onb.w - n.unit_vector()).near_zero());
+            for (a, b) in [(onb.u, onb.v), (onb.v, onb.w), (onb.w, onb.u)] {
+                assert!(a.dot(&b).abs() < 1e-12);
+            }
+            // Right handed
+            assert!((onb.u.cross(onb.v) - onb.w).near_zero());
+            assert!((onb.local(Vec3::new(0, 0, 1)) - onb.w).near_zero());
+            let a = Vec3::new(0.3, -1, 2);
+            assert!((onb.local(onb.coordinates(a)) - a).near_zero());
+        }
+    }
+}